@@ -0,0 +1,324 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Display};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use crate::error::AocError;
+use crate::inputs::Inputs;
+use crate::solution::{Part, Solver};
+
+/// Where the checked-in answers live, relative to the repository root.
+pub const DEFAULT_PATH: &str = "answers.json";
+
+/// Answers known to be correct, keyed by day, input name and part. On disk it's JSON shaped
+/// like `{"day05": {"sample": {"part1": "35", "part2": "46"}}}`. An answer of `null` marks a part
+/// that doesn't apply to that input, like a sample that only comes with one half of the puzzle.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Answers(BTreeMap<(u8, String, Part), Option<String>>);
+
+impl Answers {
+    pub fn parse(json: &str) -> Result<Answers, AocError> {
+        let invalid = |e: serde_json::Error| {
+            let line = json.lines().nth(e.line().saturating_sub(1)).unwrap_or("");
+            AocError::parse(e.line(), e.column(), e.to_string(), line)
+        };
+
+        let days: BTreeMap<String, BTreeMap<String, BTreeMap<String, Option<String>>>> =
+            serde_json::from_str(json).map_err(invalid)?;
+
+        let mut answers = Answers::default();
+
+        for (day_key, names) in days {
+            let day = day_key
+                .strip_prefix("day")
+                .and_then(|d| d.parse().ok())
+                .ok_or_else(|| key_error(json, &day_key, "a day key like \"day05\""))?;
+
+            for (name, parts) in names {
+                for (part_key, answer) in parts {
+                    let part = part_key
+                        .strip_prefix("part")
+                        .and_then(|p| p.parse().ok())
+                        .and_then(|p: u8| Part::try_from(p).ok())
+                        .ok_or_else(|| key_error(json, &part_key, "\"part1\" or \"part2\""))?;
+
+                    answers.0.insert((day, name.clone(), part), answer);
+                }
+            }
+        }
+
+        Ok(answers)
+    }
+
+    /// Reads the answers file at `path`. A missing file is the same as one with no answers in it.
+    pub fn load(path: impl AsRef<Path>) -> Result<Answers, AocError> {
+        let path = path.as_ref();
+
+        match fs::read_to_string(path) {
+            Ok(json) => Answers::parse(&json),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Answers::default()),
+            Err(e) => Err(AocError::Io(path.to_path_buf(), e.to_string())),
+        }
+    }
+
+    pub fn get(&self, day: u8, name: &str, part: Part) -> Option<&str> {
+        self.0
+            .get(&(day, name.to_string(), part))
+            .and_then(|answer| answer.as_deref())
+    }
+
+    /// Whether `part` has been marked as not applying to the input called `name`.
+    pub fn skipped(&self, day: u8, name: &str, part: Part) -> bool {
+        self.0.get(&(day, name.to_string(), part)) == Some(&None)
+    }
+
+    pub fn insert(&mut self, day: u8, name: &str, part: Part, answer: impl Into<String>) {
+        self.0
+            .insert((day, name.to_string(), part), Some(answer.into()));
+    }
+
+    pub fn skip(&mut self, day: u8, name: &str, part: Part) {
+        self.0.insert((day, name.to_string(), part), None);
+    }
+
+    /// Names of the inputs with anything recorded for `day`, sorted.
+    pub fn names(&self, day: u8) -> Vec<String> {
+        self.0
+            .keys()
+            .filter(|(d, _, _)| *d == day)
+            .map(|(_, name, _)| name.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+// the answers file is small enough that pointing at the first mention of a bad key is plenty
+fn key_error(json: &str, key: &str, expected: &str) -> AocError {
+    let quoted = format!("\"{}\"", key);
+
+    json.lines()
+        .enumerate()
+        .find_map(|(i, line)| {
+            line.find(&quoted)
+                .map(|col| AocError::parse(i + 1, col + 1, expected, line))
+        })
+        .unwrap_or_else(|| AocError::parse(1, 1, expected, ""))
+}
+
+/// Outcome of checking one solver against one input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Fail {
+        expected: String,
+        actual: String,
+    },
+    /// No answer recorded for this input yet; `actual` is what the solver came up with.
+    Unknown {
+        actual: String,
+    },
+    /// There's an answer recorded, but the input it's for isn't there to check it against.
+    Missing {
+        expected: String,
+    },
+    Error(AocError),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub day: u8,
+    pub part: Part,
+    pub name: String,
+    pub status: Status,
+}
+
+impl Check {
+    pub fn failed(&self) -> bool {
+        matches!(self.status, Status::Fail { .. } | Status::Error(_))
+    }
+}
+
+impl Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "day {:02} {} {:<12} ", self.day, self.part, self.name)?;
+
+        match &self.status {
+            Status::Pass => write!(f, "pass"),
+            Status::Fail { expected, actual } => {
+                write!(f, "FAIL    expected {}, got {}", expected, actual)
+            }
+            Status::Unknown { actual } => write!(f, "unknown {}", actual),
+            Status::Missing { expected } => {
+                write!(f, "missing input, expected {}", expected)
+            }
+            Status::Error(e) => write!(f, "ERROR   {}", e.to_string().replace('\n', "\n        ")),
+        }
+    }
+}
+
+/// Runs each of `solutions` on every input `inputs` has for its day whose name passes `include`,
+/// comparing against `answers`. Answers whose input isn't present are reported as missing, and
+/// parts marked as not applying to an input are skipped.
+pub fn verify<F>(
+    solutions: &[&dyn Solver],
+    inputs: &Inputs,
+    answers: &Answers,
+    include: F,
+) -> Vec<Check>
+where
+    F: Fn(&str) -> bool,
+{
+    let mut checks = Vec::new();
+
+    for solution in solutions {
+        let day = solution.day();
+
+        let present = inputs.names(day);
+        let names = present
+            .iter()
+            .cloned()
+            .chain(answers.names(day))
+            .collect::<BTreeSet<_>>();
+
+        for name in names.into_iter().filter(|name| include(name)) {
+            for part in Part::ALL {
+                if answers.skipped(day, &name, part) {
+                    continue;
+                }
+
+                let status = if !present.contains(&name) {
+                    match answers.get(day, &name, part) {
+                        Some(expected) => Status::Missing {
+                            expected: expected.to_string(),
+                        },
+                        None => continue,
+                    }
+                } else {
+                    match solution.solve_named(part, inputs, &name) {
+                        None => continue,
+                        Some(Err(e)) => Status::Error(e),
+                        Some(Ok(actual)) => match answers.get(day, &name, part) {
+                            Some(expected) if expected == actual => Status::Pass,
+                            Some(expected) => Status::Fail {
+                                expected: expected.to_string(),
+                                actual,
+                            },
+                            None => Status::Unknown { actual },
+                        },
+                    }
+                };
+
+                checks.push(Check {
+                    day,
+                    part,
+                    name: name.clone(),
+                    status,
+                });
+            }
+        }
+    }
+
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day06::Day06;
+    use crate::inputs::{fixtures, scratch_dir};
+
+    #[test]
+    fn test_parse() {
+        let answers = Answers::parse(
+            r#"{
+                "day01": {"sample2": {"part1": null, "part2": "281"}},
+                "day05": {"sample": {"part1": "35", "part2": "46"}},
+                "day06": {"real": {"part1": "1234"}}
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(answers.get(1, "sample2", Part::One), None);
+        assert!(answers.skipped(1, "sample2", Part::One));
+        assert!(!answers.skipped(1, "sample2", Part::Two));
+        assert_eq!(answers.get(5, "sample", Part::Two), Some("46"));
+        assert_eq!(answers.get(6, "real", Part::One), Some("1234"));
+        assert_eq!(answers.get(6, "real", Part::Two), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Answers::parse("{\n  \"day05\": {\"sample\": {\"part3\": \"35\"}}\n}"),
+            Err(AocError::parse(
+                2,
+                24,
+                "\"part1\" or \"part2\"",
+                "  \"day05\": {\"sample\": {\"part3\": \"35\"}}"
+            ))
+        );
+
+        assert!(matches!(
+            Answers::parse("{\"day05\": {\"sample\": {\"part1\": 35}}}"),
+            Err(AocError::Parse { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_verify() {
+        let dir = scratch_dir("answers");
+        fs::copy(
+            fixtures().path(6, "sample"),
+            dir.join("2023/day06.sample.txt"),
+        )
+        .unwrap();
+        fs::write(dir.join("2023/day06.broken.txt"), "Time: 7").unwrap();
+
+        let mut answers = Answers::default();
+        answers.insert(6, "sample", Part::One, "288");
+        answers.insert(6, "sample", Part::Two, "71502");
+        answers.insert(6, "real", Part::One, "1234");
+
+        let checks = verify(&[&Day06], &Inputs::new(&dir), &answers, |_| true);
+        let statuses = checks
+            .iter()
+            .map(|c| (c.name.as_str(), c.part, &c.status))
+            .collect::<Vec<_>>();
+
+        assert_eq!(statuses.len(), 5);
+        assert!(matches!(
+            statuses[0],
+            ("broken", Part::One, Status::Error(_))
+        ));
+        assert_eq!(
+            statuses[2],
+            (
+                "real",
+                Part::One,
+                &Status::Missing {
+                    expected: "1234".to_string()
+                }
+            )
+        );
+        assert_eq!(statuses[3], ("sample", Part::One, &Status::Pass));
+        assert_eq!(
+            statuses[4],
+            (
+                "sample",
+                Part::Two,
+                &Status::Fail {
+                    expected: "71502".to_string(),
+                    actual: "71503".to_string()
+                }
+            )
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
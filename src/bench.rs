@@ -0,0 +1,192 @@
+use std::fmt::Write;
+use std::time::Duration;
+
+use serde_json::json;
+
+use crate::solution::Part;
+
+/// Raw timings from running one part of a day's solution repeatedly: `parse[i]` and `solve[i]`
+/// are the two phases of run `i`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Samples {
+    pub parse: Vec<Duration>,
+    pub solve: Vec<Duration>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    /// None if there are no samples. The median of an even number of samples is the mean of
+    /// the middle two.
+    pub fn of(samples: &[Duration]) -> Option<Stats> {
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+
+        let mid = sorted.len() / 2;
+        let median = if sorted.len().is_multiple_of(2) {
+            (*sorted.get(mid.checked_sub(1)?)? + sorted[mid]) / 2
+        } else {
+            sorted[mid]
+        };
+
+        Some(Stats {
+            min: *sorted.first()?,
+            median,
+            max: *sorted.last()?,
+        })
+    }
+}
+
+/// Summary of benchmarking one part of one day against one input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Measurement {
+    pub day: u8,
+    pub part: Part,
+    pub name: String,
+    pub runs: usize,
+    pub parse: Stats,
+    pub solve: Stats,
+}
+
+impl Measurement {
+    pub fn new(day: u8, part: Part, name: &str, samples: &Samples) -> Option<Measurement> {
+        Some(Measurement {
+            day,
+            part,
+            name: name.to_string(),
+            runs: samples.solve.len(),
+            parse: Stats::of(&samples.parse)?,
+            solve: Stats::of(&samples.solve)?,
+        })
+    }
+}
+
+/// Renders `measurements` as a plain text table, one row per day and part.
+pub fn table(measurements: &[Measurement]) -> String {
+    let mut out = format!(
+        "{:<4} {:<4} {:<12} {:>5} | {:>10} {:>10} {:>10} | {:>10} {:>10} {:>10}\n",
+        "day", "part", "input", "runs", "parse min", "median", "max", "solve min", "median", "max"
+    );
+    out.push_str(&"-".repeat(out.len() - 1));
+
+    for m in measurements {
+        let _ = write!(
+            out,
+            "\n{:<4} {:<4} {:<12} {:>5} | {:>10} {:>10} {:>10} | {:>10} {:>10} {:>10}",
+            m.day,
+            m.part.number(),
+            m.name,
+            m.runs,
+            format_duration(m.parse.min),
+            format_duration(m.parse.median),
+            format_duration(m.parse.max),
+            format_duration(m.solve.min),
+            format_duration(m.solve.median),
+            format_duration(m.solve.max),
+        );
+    }
+
+    out
+}
+
+/// Renders `measurements` as a JSON array, with all durations in nanoseconds.
+pub fn json(measurements: &[Measurement]) -> String {
+    let stats = |s: &Stats| {
+        json!({
+            "min_ns": s.min.as_nanos() as u64,
+            "median_ns": s.median.as_nanos() as u64,
+            "max_ns": s.max.as_nanos() as u64,
+        })
+    };
+
+    let rows = measurements
+        .iter()
+        .map(|m| {
+            json!({
+                "day": m.day,
+                "part": m.part.number(),
+                "input": m.name,
+                "runs": m.runs,
+                "parse": stats(&m.parse),
+                "solve": stats(&m.solve),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    serde_json::to_string_pretty(&rows).unwrap_or_default()
+}
+
+// e.g. "812ns", "45.3µs", "1.20ms", "3.51s"
+fn format_duration(d: Duration) -> String {
+    let nanos = d.as_nanos();
+
+    if nanos < 1_000 {
+        format!("{}ns", nanos)
+    } else if nanos < 1_000_000 {
+        format!("{:.1}µs", nanos as f64 / 1e3)
+    } else if nanos < 1_000_000_000 {
+        format!("{:.2}ms", nanos as f64 / 1e6)
+    } else {
+        format!("{:.2}s", d.as_secs_f64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day06::Day06;
+    use crate::inputs::fixtures;
+    use crate::solution::Solver;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn test_stats() {
+        assert_eq!(
+            Stats::of(&[ms(5), ms(1), ms(3)]),
+            Some(Stats {
+                min: ms(1),
+                median: ms(3),
+                max: ms(5)
+            })
+        );
+        assert_eq!(
+            Stats::of(&[ms(4), ms(1), ms(2), ms(9)]).map(|s| s.median),
+            Some(ms(3))
+        );
+        assert_eq!(Stats::of(&[]), None);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_nanos(812)), "812ns");
+        assert_eq!(format_duration(Duration::from_nanos(45_300)), "45.3µs");
+        assert_eq!(format_duration(ms(1200)), "1.20s");
+    }
+
+    #[test]
+    fn test_bench_solver() {
+        let input = fixtures().read(6, "sample").unwrap();
+        let samples = Day06.bench(Part::Two, &input, 3).unwrap().unwrap();
+
+        assert_eq!(samples.parse.len(), 3);
+        assert_eq!(samples.solve.len(), 3);
+
+        let m = Measurement::new(6, Part::Two, "sample", &samples).unwrap();
+        let rendered = json(std::slice::from_ref(&m));
+
+        assert!(table(&[m])
+            .lines()
+            .nth(2)
+            .unwrap()
+            .starts_with("6    2    sample"));
+        assert!(rendered.contains("\"median_ns\""));
+    }
+}
@@ -0,0 +1,768 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::iter;
+
+use nom::character::complete::one_of;
+use nom::combinator::map;
+use nom::error::context;
+use nom::multi::count;
+
+use crate::error::AocError;
+use crate::parsing::{parse_all, ParseResult};
+
+/// One card: a rank, and a suit in games that have them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Card {
+    pub rank: char,
+    pub suit: Option<char>,
+}
+
+impl Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.rank)?;
+
+        match self.suit {
+            Some(suit) => write!(f, "{}", suit),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The cards of one hand, in the order they were dealt.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Cards(pub Vec<Card>);
+
+/// e.g. "32T3K", or "AhKhQhJhTh" in a game with suits
+impl Display for Cards {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|card| write!(f, "{}", card))
+    }
+}
+
+/// The kinds of hand the presets know about, so code outside the engine can tell which one a
+/// hand made without going by its name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HandKind {
+    HighCard,
+    OnePair,
+    TwoPair,
+    ThreeOfAKind,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOfAKind,
+    StraightFlush,
+    FiveOfAKind,
+}
+
+impl Display for HandKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            HandKind::HighCard => "high card",
+            HandKind::OnePair => "one pair",
+            HandKind::TwoPair => "two pair",
+            HandKind::ThreeOfAKind => "three of a kind",
+            HandKind::Straight => "straight",
+            HandKind::Flush => "flush",
+            HandKind::FullHouse => "full house",
+            HandKind::FourOfAKind => "four of a kind",
+            HandKind::StraightFlush => "straight flush",
+            HandKind::FiveOfAKind => "five of a kind",
+        })
+    }
+}
+
+/// One kind of hand. A hand is of this kind if its wildcards can be played so that it has
+/// groups of equal ranks at least as big as `groups`, all its ranks in a row if `straight`,
+/// and all its cards in one suit if `flush`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HandRule {
+    pub kind: HandKind,
+    /// Sizes of the groups of equal ranks, e.g. `[3, 2]` for a full house. Straights leave
+    /// this empty, since all their ranks differ.
+    pub groups: &'static [usize],
+    pub straight: bool,
+    pub flush: bool,
+}
+
+impl HandRule {
+    pub const fn groups(kind: HandKind, groups: &'static [usize]) -> HandRule {
+        HandRule {
+            kind,
+            groups,
+            straight: false,
+            flush: false,
+        }
+    }
+
+    pub const fn straight(kind: HandKind) -> HandRule {
+        HandRule {
+            kind,
+            groups: &[],
+            straight: true,
+            flush: false,
+        }
+    }
+
+    pub const fn flush(kind: HandKind) -> HandRule {
+        HandRule {
+            kind,
+            groups: &[],
+            straight: false,
+            flush: true,
+        }
+    }
+
+    pub const fn straight_flush(kind: HandKind) -> HandRule {
+        HandRule {
+            kind,
+            groups: &[],
+            straight: true,
+            flush: true,
+        }
+    }
+}
+
+/// How two hands of the same kind compare.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tiebreak {
+    /// Card by card in the order they were dealt, as in Camel Cards. Wildcards count as their
+    /// own rank.
+    Dealt,
+    /// Biggest group first, then stronger ranks first, as in poker. Wildcards count as the
+    /// cards they're played as, and a straight by its top card.
+    Grouped,
+}
+
+const CAMEL_CARDS_TYPES: &[HandRule] = &[
+    HandRule::groups(HandKind::HighCard, &[]),
+    HandRule::groups(HandKind::OnePair, &[2]),
+    HandRule::groups(HandKind::TwoPair, &[2, 2]),
+    HandRule::groups(HandKind::ThreeOfAKind, &[3]),
+    HandRule::groups(HandKind::FullHouse, &[3, 2]),
+    HandRule::groups(HandKind::FourOfAKind, &[4]),
+    HandRule::groups(HandKind::FiveOfAKind, &[5]),
+];
+
+const POKER_TYPES: &[HandRule] = &[
+    HandRule::groups(HandKind::HighCard, &[]),
+    HandRule::groups(HandKind::OnePair, &[2]),
+    HandRule::groups(HandKind::TwoPair, &[2, 2]),
+    HandRule::groups(HandKind::ThreeOfAKind, &[3]),
+    HandRule::straight(HandKind::Straight),
+    HandRule::flush(HandKind::Flush),
+    HandRule::groups(HandKind::FullHouse, &[3, 2]),
+    HandRule::groups(HandKind::FourOfAKind, &[4]),
+    HandRule::straight_flush(HandKind::StraightFlush),
+];
+
+/// Everything that decides how hands of a card game rank against each other.
+///
+/// Sort keys pack the kind of hand and 4 bits per card into a `u128`, so a game can have at
+/// most 16 kinds of hand, 15 ranks and 31 cards to a hand, which [`Game::check`] enforces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Game {
+    pub name: &'static str,
+    pub hand_size: usize,
+    /// Every rank, strongest first.
+    pub ranks: &'static str,
+    /// Every suit, or empty in a game without suits.
+    pub suits: &'static str,
+    /// Ranks that are wildcards, played as whatever card makes the best kind of hand.
+    pub wild: &'static str,
+    /// Whether the strongest rank may also sit below the weakest in a straight, as the ace
+    /// does in A2345.
+    pub ace_low: bool,
+    pub tiebreak: Tiebreak,
+    /// Every kind of hand, weakest first. A hand is the strongest kind it can make, so the
+    /// first should match anything.
+    pub hand_types: &'static [HandRule],
+}
+
+impl Game {
+    /// Day 7 part 1, where J is an ordinary jack.
+    pub const CAMEL_CARDS: Game = Game {
+        name: "Camel Cards",
+        hand_size: 5,
+        ranks: "AKQJT98765432",
+        suits: "",
+        wild: "",
+        ace_low: false,
+        tiebreak: Tiebreak::Dealt,
+        hand_types: CAMEL_CARDS_TYPES,
+    };
+
+    /// Day 7 part 2, where J is a joker: wild, but the weakest card on its own.
+    pub const CAMEL_CARDS_JOKERS: Game = Game {
+        name: "Camel Cards with jokers",
+        ranks: "AKQT98765432J",
+        wild: "J",
+        ..Game::CAMEL_CARDS
+    };
+
+    /// Five card poker without wildcards, cards written as rank then suit, e.g. "Th".
+    pub const POKER: Game = Game {
+        name: "poker",
+        hand_size: 5,
+        ranks: "AKQJT98765432",
+        suits: "cdhs",
+        wild: "",
+        ace_low: true,
+        tiebreak: Tiebreak::Grouped,
+        hand_types: POKER_TYPES,
+    };
+
+    /// Whether hands of this game can be classified and given sort keys.
+    pub fn check(&self) -> Result<(), AocError> {
+        let invalid = |why: String| Err(AocError::InvalidGame(format!("{}: {}", self.name, why)));
+        let distinct = |s: &str| s.chars().all(|c| s.matches(c).count() == 1);
+
+        if !(1..=31).contains(&self.hand_size) {
+            return invalid(format!("{} cards to a hand, not 1 to 31", self.hand_size));
+        }
+
+        if self.ranks.is_empty() || self.ranks.chars().count() > 15 || !distinct(self.ranks) {
+            return invalid(format!(
+                "ranks {:?} aren't 1 to 15 distinct labels",
+                self.ranks
+            ));
+        }
+
+        if !distinct(self.suits) {
+            return invalid(format!("suits {:?} aren't distinct", self.suits));
+        }
+
+        if let Some(c) = self.wild.chars().find(|&c| !self.ranks.contains(c)) {
+            return invalid(format!("wildcard {} isn't one of the ranks", c));
+        }
+
+        if self.hand_types.is_empty() || self.hand_types.len() > 16 {
+            return invalid(format!(
+                "{} kinds of hand, not 1 to 16",
+                self.hand_types.len()
+            ));
+        }
+
+        match self.hand_types.iter().find(|rule| {
+            rule.groups.iter().sum::<usize>() > self.hand_size
+                || (rule.flush && self.suits.is_empty())
+        }) {
+            Some(rule) => invalid(format!("no hand can be a {}", rule.kind)),
+            None => Ok(()),
+        }
+    }
+
+    /// A single card, e.g. "K", or "Kh" in a game with suits.
+    pub fn card<'a>(&'a self) -> impl FnMut(&'a str) -> ParseResult<'a, Card> + 'a {
+        move |input| {
+            let (input, rank) = context("a card", one_of(self.ranks))(input)?;
+
+            if self.suits.is_empty() {
+                return Ok((input, Card { rank, suit: None }));
+            }
+
+            let (input, suit) = context("a suit", one_of(self.suits))(input)?;
+
+            Ok((
+                input,
+                Card {
+                    rank,
+                    suit: Some(suit),
+                },
+            ))
+        }
+    }
+
+    /// A whole hand with the cards written back to back, e.g. "32T3K".
+    pub fn cards<'a>(&'a self) -> impl FnMut(&'a str) -> ParseResult<'a, Cards> + 'a {
+        map(count(self.card(), self.hand_size), Cards)
+    }
+
+    pub fn parse_cards(&self, s: &str) -> Result<Cards, AocError> {
+        parse_all(self.cards(), s)
+    }
+
+    /// The strongest kind of hand `cards` can make, and how its wildcards are played for it.
+    pub fn classify(&self, cards: &Cards) -> Classification {
+        let (hand_type, fit) = self.evaluate(cards);
+
+        Classification {
+            // a game without any kinds of hand, which `check` rejects, makes everything high card
+            kind: self
+                .hand_types
+                .get(hand_type)
+                .map_or(HandKind::HighCard, |rule| rule.kind),
+            wild: cards
+                .0
+                .iter()
+                .copied()
+                .filter(|c| self.is_wild(c))
+                .collect(),
+            played_as: fit.wild_as,
+        }
+    }
+
+    /// Packs the hand into one integer that sorts the same way the hands do: the kind of hand
+    /// above 4 bits for each card, the first of them highest. Only meaningful for games that
+    /// pass [`Game::check`].
+    pub fn key(&self, cards: &Cards) -> u128 {
+        let (hand_type, fit) = self.evaluate(cards);
+
+        let strengths = match self.tiebreak {
+            Tiebreak::Dealt => cards.0.iter().map(|c| self.strength(c.rank)).collect(),
+            Tiebreak::Grouped => fit
+                .straight
+                .unwrap_or_else(|| self.grouped(cards, &fit.wild_as)),
+        };
+
+        strengths
+            .into_iter()
+            .fold(hand_type as u128, |key, s| key << 4 | s as u128)
+    }
+
+    /// Every item with its rank, weakest first from rank 1, where `cards` picks out an item's
+    /// hand. Identical hands are still ranked one after the other, in the order given.
+    pub fn rank<'a, T>(
+        &self,
+        items: &'a [T],
+        cards: impl Fn(&T) -> &Cards,
+    ) -> Result<Vec<(u32, &'a T)>, AocError> {
+        self.check()?;
+
+        let mut ranked: Vec<&T> = items.iter().collect();
+
+        // stable, which is what keeps identical hands in the order given
+        ranked.sort_by_cached_key(|&item| self.key(cards(item)));
+
+        ranked
+            .into_iter()
+            .enumerate()
+            .map(|(i, item)| {
+                u32::try_from(i + 1)
+                    .map(|rank| (rank, item))
+                    .map_err(|_| AocError::Overflow("hand rank"))
+            })
+            .collect()
+    }
+
+    // higher is stronger, ranks outside `ranks` are weaker than all of them
+    fn strength(&self, rank: char) -> u8 {
+        self.ranks
+            .chars()
+            .position(|r| r == rank)
+            .map_or(0, |i| (self.ranks.chars().count() - i) as u8)
+    }
+
+    fn is_wild(&self, card: &Card) -> bool {
+        self.wild.contains(card.rank)
+    }
+
+    // the strongest kind of hand that fits, or the weakest if none does
+    fn evaluate(&self, cards: &Cards) -> (usize, Fit) {
+        let natural: Vec<&Card> = cards.0.iter().filter(|c| !self.is_wild(c)).collect();
+        let deal = Deal {
+            wilds: cards.0.len() - natural.len(),
+            counts: self.counts(natural.iter().map(|c| c.rank)),
+            natural,
+        };
+
+        self.hand_types
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(i, rule)| Some((i, self.fit(rule, &deal)?)))
+            .unwrap_or_default()
+    }
+
+    fn fit(&self, rule: &HandRule, deal: &Deal) -> Option<Fit> {
+        let natural = &deal.natural;
+
+        let suit = if rule.flush {
+            let first = natural
+                .first()
+                .map_or(self.suits.chars().next(), |c| c.suit);
+
+            if first.is_none() || natural.iter().any(|c| c.suit != first) {
+                return None;
+            }
+
+            first
+        } else {
+            None
+        };
+
+        let (ranks, straight) = if rule.straight {
+            let (ranks, strengths) = self.straight(deal)?;
+            (ranks, Some(strengths))
+        } else {
+            (self.groups(rule.groups, deal)?, None)
+        };
+
+        Some(Fit {
+            wild_as: ranks.into_iter().map(|rank| Card { rank, suit }).collect(),
+            straight,
+        })
+    }
+
+    // what the wildcards play as to make groups of at least `sizes`, if there are enough of
+    // them. The biggest group wanted goes to the biggest group there is and so on, and any
+    // wildcards left over join the biggest, which never makes the hand worse.
+    fn groups(&self, sizes: &[usize], deal: &Deal) -> Option<Vec<char>> {
+        let (counts, wilds) = (&deal.counts, deal.wilds);
+        let mut unused = self
+            .ranks
+            .chars()
+            .filter(|&r| !self.wild.contains(r) && counts.iter().all(|&(c, _)| c != r));
+
+        let mut sizes = sizes.to_vec();
+        sizes.sort_by(|a, b| b.cmp(a));
+
+        let mut wild_as = Vec::new();
+        let mut biggest = counts.first().map(|&(rank, _)| rank);
+
+        for (i, &size) in sizes.iter().enumerate() {
+            let (rank, have) = match counts.get(i) {
+                Some(&(rank, n)) => (rank, n),
+                None => (unused.next()?, 0),
+            };
+
+            biggest.get_or_insert(rank);
+            wild_as.extend(iter::repeat_n(rank, size.saturating_sub(have)));
+        }
+
+        if wild_as.len() > wilds {
+            return None;
+        }
+
+        if wild_as.len() < wilds {
+            let rank = biggest.or_else(|| unused.next())?;
+            wild_as.extend(iter::repeat_n(rank, wilds - wild_as.len()));
+        }
+
+        Some(wild_as)
+    }
+
+    // what the wildcards play as to fill the highest straight the natural cards fit in, with
+    // the strengths of that straight from the top
+    fn straight(&self, deal: &Deal) -> Option<(Vec<char>, Vec<u8>)> {
+        let (natural, wilds) = (&deal.natural, deal.wilds);
+        let mut run: Vec<(char, u8)> = self
+            .ranks
+            .chars()
+            .rev()
+            .filter(|&r| !self.wild.contains(r))
+            .map(|r| (r, self.strength(r)))
+            .collect();
+
+        if self.ace_low {
+            if let Some(&(top, _)) = run.last() {
+                run.insert(0, (top, 0));
+            }
+        }
+
+        // a window with every natural rank in it and a gap for each wildcard, which also
+        // rules out pairs and windows with a rank twice
+        run.windows(natural.len() + wilds).rev().find_map(|window| {
+            let missing = window
+                .iter()
+                .rev()
+                .map(|&(r, _)| r)
+                .filter(|&r| natural.iter().all(|c| c.rank != r))
+                .collect::<Vec<_>>();
+
+            let all_in = natural
+                .iter()
+                .all(|c| window.iter().any(|&(r, _)| r == c.rank));
+
+            (all_in && missing.len() == wilds)
+                .then(|| (missing, window.iter().rev().map(|&(_, s)| s).collect()))
+        })
+    }
+
+    // strengths of the cards as played, biggest group first and stronger ranks first
+    fn grouped(&self, cards: &Cards, wild_as: &[Card]) -> Vec<u8> {
+        let played = cards
+            .0
+            .iter()
+            .filter(|c| !self.is_wild(c))
+            .chain(wild_as)
+            .map(|c| c.rank);
+
+        self.counts(played)
+            .into_iter()
+            .flat_map(|(rank, n)| iter::repeat_n(self.strength(rank), n))
+            .collect()
+    }
+
+    // how many of each rank, most first and then strongest first
+    fn counts(&self, ranks: impl Iterator<Item = char>) -> Vec<(char, usize)> {
+        let mut counts = HashMap::new();
+        ranks.for_each(|r| *counts.entry(r).or_insert(0) += 1);
+
+        let mut counts: Vec<(char, usize)> = counts.into_iter().collect();
+        counts.sort_by_key(|&(rank, n)| Reverse((n, self.strength(rank))));
+        counts
+    }
+}
+
+// a hand split into its wildcards and the rest, worked out once for all kinds of hand
+struct Deal<'a> {
+    natural: Vec<&'a Card>,
+    wilds: usize,
+    counts: Vec<(char, usize)>,
+}
+
+#[derive(Debug, Default)]
+struct Fit {
+    // what each wildcard plays as, in the order they were dealt
+    wild_as: Vec<Card>,
+    // for a straight, its strengths from the top, where a low ace is the weakest
+    straight: Option<Vec<u8>>,
+}
+
+/// The kind of a hand along with how its wildcards were played to get there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Classification {
+    /// How strong it is depends on the game, see [`Game::hand_types`].
+    pub kind: HandKind,
+    /// The hand's wildcards, in the order they were dealt.
+    pub wild: Vec<Card>,
+    /// What each of them is played as.
+    pub played_as: Vec<Card>,
+}
+
+/// e.g. "full house", "JJ → A makes four of a kind" or "22 → Jh Th makes straight flush"
+impl Display for Classification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.wild.is_empty() {
+            let mut played_as: Vec<String> = Vec::new();
+
+            for card in &self.played_as {
+                if !played_as.contains(&card.to_string()) {
+                    played_as.push(card.to_string());
+                }
+            }
+
+            write!(
+                f,
+                "{} → {} makes ",
+                Cards(self.wild.clone()),
+                played_as.join(" ")
+            )?;
+        }
+
+        write!(f, "{}", self.kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn poker(s: &str) -> Cards {
+        Game::POKER.parse_cards(s).unwrap()
+    }
+
+    fn ranked(game: &Game, hands: &[&str]) -> Vec<String> {
+        let hands = hands
+            .iter()
+            .map(|s| game.parse_cards(s).unwrap())
+            .collect::<Vec<_>>();
+
+        game.rank(&hands, |h| h)
+            .unwrap()
+            .into_iter()
+            .map(|(_, h)| h.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            poker("AhTc9d8s7c").0[..2],
+            [
+                Card {
+                    rank: 'A',
+                    suit: Some('h')
+                },
+                Card {
+                    rank: 'T',
+                    suit: Some('c')
+                }
+            ]
+        );
+        assert_eq!(poker("AhKhQhJhTh").to_string(), "AhKhQhJhTh");
+        assert_eq!(
+            Game::POKER.parse_cards("AhKxQhJhTh"),
+            Err(AocError::parse(1, 4, "a suit", "AhKxQhJhTh"))
+        );
+        assert_eq!(
+            Game::CAMEL_CARDS.parse_cards("32T3"),
+            Err(AocError::parse(1, 5, "a card", "32T3"))
+        );
+    }
+
+    #[test]
+    fn test_poker_kinds() {
+        let kind = |s: &str| Game::POKER.classify(&poker(s)).kind.to_string();
+
+        assert_eq!(kind("AhKhQhJhTh"), "straight flush");
+        assert_eq!(kind("Ah2h3h4h5h"), "straight flush");
+        assert_eq!(kind("9c9d9h9s2c"), "four of a kind");
+        assert_eq!(kind("9c9d9h2s2c"), "full house");
+        assert_eq!(kind("2h7h9hJhKh"), "flush");
+        assert_eq!(kind("Ac2d3h4s5c"), "straight");
+        assert_eq!(kind("Tc9dJhQsKc"), "straight");
+        assert_eq!(kind("Kc2d3h4sAc"), "high card");
+        assert_eq!(kind("QcAd2h3s4c"), "high card");
+        assert_eq!(kind("9c9d9h2s3c"), "three of a kind");
+        assert_eq!(kind("9c9d2h2s3c"), "two pair");
+        assert_eq!(kind("9c9d2h4s3c"), "one pair");
+    }
+
+    #[test]
+    fn test_poker_ranking() {
+        assert_eq!(
+            ranked(
+                &Game::POKER,
+                &[
+                    "2c3d4h5s6c", // six-high straight
+                    "KhKd2c3s4h", // pair of kings
+                    "Ac2d3h4s5c", // five-high straight, the ace plays low
+                    "QhQdAcKs4h", // pair of queens, despite the ace
+                    "AhAd2c2d3s", // aces up
+                    "KhKdQcQdAs", // kings up
+                    "2h5h9hJhKh", // flush
+                    "3c3d3h2s2c", // threes full
+                    "2c2d2h3s3c", // twos full
+                ]
+            ),
+            [
+                "QhQdAcKs4h",
+                "KhKd2c3s4h",
+                "KhKdQcQdAs",
+                "AhAd2c2d3s",
+                "Ac2d3h4s5c",
+                "2c3d4h5s6c",
+                "2h5h9hJhKh",
+                "2c2d2h3s3c",
+                "3c3d3h2s2c",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_wild_poker() {
+        let deuces = Game {
+            name: "deuces wild",
+            wild: "2",
+            ..Game::POKER
+        };
+        let explain = |s: &str| deuces.classify(&deuces.parse_cards(s).unwrap()).to_string();
+
+        assert_eq!(explain("2h2dAhKhQh"), "2h2d → Jh Th makes straight flush");
+        assert_eq!(explain("2hAdAhKsQc"), "2h → A makes three of a kind");
+        assert_eq!(explain("2h9d8h6s5c"), "2h → 7 makes straight");
+        assert_eq!(
+            explain("2h2d2c2sKc"),
+            "2h2d2c2s → Ac Qc Jc Tc makes straight flush"
+        );
+        assert_eq!(explain("2h3d4h6s7c"), "2h → 5 makes straight");
+
+        // a wild deuce playing as an ace beats a real king
+        assert_eq!(
+            ranked(&deuces, &["KhKdKc9s4c", "2hAdAc9s4c"]),
+            ["KhKdKc9s4c", "2hAdAc9s4c"]
+        );
+    }
+
+    #[test]
+    fn test_camel_cards_preset() {
+        let explain =
+            |game: &Game, s: &str| game.classify(&game.parse_cards(s).unwrap()).to_string();
+
+        assert_eq!(
+            explain(&Game::CAMEL_CARDS_JOKERS, "AAJJ2"),
+            "JJ → A makes four of a kind"
+        );
+        assert_eq!(explain(&Game::CAMEL_CARDS, "AAJJ2"), "two pair");
+
+        // no straights in Camel Cards
+        assert_eq!(explain(&Game::CAMEL_CARDS, "23456"), "high card");
+
+        assert_eq!(
+            ranked(
+                &Game::CAMEL_CARDS_JOKERS,
+                &["32T3K", "T55J5", "KK677", "KTJJT", "QQQJA"]
+            ),
+            ["32T3K", "KK677", "T55J5", "QQQJA", "KTJJT"]
+        );
+    }
+
+    #[test]
+    fn test_other_hand_sizes() {
+        // three cards, where a straight beats three of a kind, as in three card poker
+        const THREE_CARD_TYPES: &[HandRule] = &[
+            HandRule::groups(HandKind::HighCard, &[]),
+            HandRule::groups(HandKind::OnePair, &[2]),
+            HandRule::flush(HandKind::Flush),
+            HandRule::straight(HandKind::Straight),
+            HandRule::groups(HandKind::ThreeOfAKind, &[3]),
+            HandRule::straight_flush(HandKind::StraightFlush),
+        ];
+
+        let three_card = Game {
+            name: "three card poker",
+            hand_size: 3,
+            hand_types: THREE_CARD_TYPES,
+            ..Game::POKER
+        };
+
+        assert_eq!(
+            ranked(&three_card, &["QsJhTc", "KcKdKh", "2h7h9h", "AcAd2s"]),
+            ["AcAd2s", "2h7h9h", "QsJhTc", "KcKdKh"]
+        );
+        assert_eq!(
+            three_card.parse_cards("QsJhTcTd"),
+            Err(AocError::parse(1, 7, "end of input", "QsJhTcTd"))
+        );
+    }
+
+    #[test]
+    fn test_check() {
+        assert_eq!(Game::CAMEL_CARDS.check(), Ok(()));
+        assert_eq!(Game::CAMEL_CARDS_JOKERS.check(), Ok(()));
+        assert_eq!(Game::POKER.check(), Ok(()));
+
+        let too_many_ranks = Game {
+            ranks: "ZYXWVAKQJT98765432",
+            ..Game::CAMEL_CARDS
+        };
+        let no_suits = Game {
+            suits: "",
+            ..Game::POKER
+        };
+        let stray_wild = Game {
+            wild: "*",
+            ..Game::CAMEL_CARDS
+        };
+
+        assert!(matches!(
+            too_many_ranks.check(),
+            Err(AocError::InvalidGame(_))
+        ));
+        assert_eq!(
+            no_suits.check(),
+            Err(AocError::InvalidGame(
+                "poker: no hand can be a flush".to_string()
+            ))
+        );
+        assert_eq!(
+            stray_wild.rank(&[Cards(vec![])], |h| h),
+            Err(AocError::InvalidGame(
+                "Camel Cards: wildcard * isn't one of the ranks".to_string()
+            ))
+        );
+    }
+}
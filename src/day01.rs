@@ -1,17 +1,25 @@
-use std::num::ParseIntError;
+use crate::error::{non_empty, AocError};
+use crate::solution::Solution;
+
+#[aoc_generator(day1)]
+pub fn input_generator(input: &str) -> Result<Vec<String>, AocError> {
+    Ok(non_empty(input)?.lines().map(str::to_string).collect())
+}
 
 #[aoc(day1, part1)]
-pub fn solve1(input: &str) -> i32 {
+pub fn solve1(input: &[String]) -> Result<i32, AocError> {
     input
-        .lines()
-        .map(|line| line.chars().filter(|c| c.is_digit(10)))
-        .map(|digits| int_from_first_and_last_digits(digits))
-        .sum::<Result<_, _>>()
-        .unwrap()
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            int_from_first_and_last_digits(line.chars().filter(|c| c.is_ascii_digit()))
+                .ok_or_else(|| no_digits(i + 1, line))
+        })
+        .sum()
 }
 
 #[aoc(day1, part2)]
-pub fn solve2(input: &str) -> i32 {
+pub fn solve2(input: &[String]) -> Result<i32, AocError> {
     fn num_char_at_index(line: &str, i: usize) -> Option<char> {
         fn word_num(substring: &str) -> Option<char> {
             let words: Vec<&str> = vec![
@@ -29,30 +37,71 @@ pub fn solve2(input: &str) -> i32 {
 
         line.chars()
             .nth(i)
-            .filter(|c| c.is_digit(10))
-            .or(line.get(i..).and_then(|sub| word_num(sub)))
+            .filter(|c| c.is_ascii_digit())
+            .or(line.get(i..).and_then(word_num))
     }
 
     input
-        .lines()
-        .map(|line| {
-            line.char_indices()
-                .filter_map(|(i, _)| num_char_at_index(line, i))
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let digits = line
+                .char_indices()
+                .filter_map(|(j, _)| num_char_at_index(line, j));
+
+            int_from_first_and_last_digits(digits).ok_or_else(|| no_digits(i + 1, line))
         })
-        .map(|digits| int_from_first_and_last_digits(digits))
-        .sum::<Result<_, _>>()
-        .unwrap()
+        .sum()
 }
 
-fn int_from_first_and_last_digits<I>(digits: I) -> Result<i32, ParseIntError>
+fn int_from_first_and_last_digits<I>(digits: I) -> Option<i32>
 where
     I: Iterator<Item = char>,
 {
     let all_ints = digits.collect::<Vec<_>>();
-    let ints = vec![
-        all_ints.first().unwrap().to_ascii_lowercase(),
-        all_ints.last().unwrap().to_ascii_lowercase(),
-    ];
+    let ints = [all_ints.first()?, all_ints.last()?];
+
+    ints.into_iter().collect::<String>().parse::<i32>().ok()
+}
+
+fn no_digits(line_number: usize, line: &str) -> AocError {
+    AocError::parse(line_number, 1, "a digit", line)
+}
+
+/// Which characters count as digits is the difference between the parts, so parsing only splits
+/// the input into lines and finding the digits counts as solving.
+pub struct Day01;
+
+impl Solution for Day01 {
+    const DAY: u8 = 1;
+    const TITLE: &'static str = "Trebuchet?!";
 
-    ints.into_iter().collect::<String>().parse::<i32>()
+    type Input = Vec<String>;
+    type Output = i32;
+
+    fn parse(input: &str) -> Result<Self::Input, AocError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Output, AocError> {
+        solve1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Option<Result<Self::Output, AocError>> {
+        Some(solve2(input))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_without_digits() {
+        assert_eq!(
+            solve1(&input_generator("1abc2\npqrstu\na1b2c3d4e5f").unwrap()),
+            Err(AocError::parse(2, 1, "a digit", "pqrstu"))
+        );
+        assert_eq!(input_generator(""), Err(AocError::EmptyInput));
+    }
 }
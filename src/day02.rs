@@ -1,13 +1,17 @@
 use std::collections::HashMap;
 use std::str::FromStr;
 
-use nom::bytes::complete::{tag, take_till};
-use nom::character::complete::{alpha1, digit1, space1};
-use nom::combinator::map_res;
-use nom::error::Error;
-use nom::multi::{separated_list0, separated_list1};
-use nom::sequence::separated_pair;
-use nom::{Finish, IResult};
+use nom::branch::alt;
+use nom::bytes::complete::tag;
+use nom::character::complete::space1;
+use nom::combinator::{cut, peek, rest};
+use nom::error::{context, VerboseError, VerboseErrorKind};
+use nom::multi::separated_list1;
+use nom::sequence::{pair, separated_pair};
+
+use crate::error::AocError;
+use crate::parsing::{parse_all, parse_lines, unsigned, ParseResult};
+use crate::solution::Solution;
 
 #[derive(Debug, PartialEq)]
 pub struct Game {
@@ -23,34 +27,39 @@ pub struct Draw {
 }
 
 // e.g. "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green"
-fn parse_game(s: &str) -> IResult<&str, Game> {
-    let (s, _) = tag("Game ")(s)?;
-    let (s, game_id) = map_res(digit1, u8::from_str)(s)?;
-    let (s, _) = tag(": ")(s)?;
-    let (s, draws) = separated_list0(tag("; "), take_till(|c| c == ';'))(s)?;
+fn parse_game(s: &str) -> ParseResult<'_, Game> {
+    let (s, _) = context("\"Game \"", tag("Game "))(s)?;
+    let (s, game_id) = context("game id", unsigned)(s)?;
+    let (s, _) = context("\": \"", tag(": "))(s)?;
+    let (s, draws) = separated_list1(tag("; "), cut(parse_draw))(s)?;
 
-    let parsed_draws = draws.into_iter().flat_map(Draw::from_str);
-
-    let game = Game {
-        id: game_id,
-        draws: parsed_draws.collect(),
-    };
+    let game = Game { id: game_id, draws };
 
     Ok((s, game))
 }
 
 // e.g. "3 green, 4 blue, 1 red"
-fn parse_draw(s: &str) -> IResult<&str, Draw> {
-    let (s, draws) = separated_list1(
-        tag(", "),
-        separated_pair(map_res(digit1, u8::from_str), space1, alpha1),
-    )(s)?;
+fn parse_draw(s: &str) -> ParseResult<'_, Draw> {
+    let count = context("cube count", unsigned::<u8>);
+    let colour = context(
+        "cube colour (red, green or blue)",
+        alt((tag("red"), tag("green"), tag("blue"))),
+    );
+
+    // where each colour starts, to point at one that repeats
+    let entry = separated_pair(count, space1, pair(peek(rest), colour));
+    let (s, draws) = separated_list1(tag(", "), cut(entry))(s)?;
 
     let mut counts = HashMap::with_capacity(3);
 
-    draws
-        .iter()
-        .for_each(|&(count, colour)| *counts.entry(colour).or_insert(0) += count);
+    for (count, (at, colour)) in draws {
+        // "2 red, 3 red" is most likely a typo, and adding them up could overflow
+        if counts.insert(colour, count).is_some() {
+            return Err(nom::Err::Failure(VerboseError {
+                errors: vec![(at, VerboseErrorKind::Context("a colour not already drawn"))],
+            }));
+        }
+    }
 
     Ok((
         s,
@@ -71,16 +80,10 @@ impl Game {
 }
 
 impl FromStr for Game {
-    type Err = Error<String>;
+    type Err = AocError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match parse_game(s).finish() {
-            Ok((_, game)) => Ok(game),
-            Err(Error { input, code }) => Err(Error {
-                input: input.to_string(),
-                code,
-            }),
-        }
+        parse_all(parse_game, s)
     }
 }
 
@@ -91,44 +94,38 @@ impl Draw {
 }
 
 impl FromStr for Draw {
-    type Err = Error<String>;
+    type Err = AocError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match parse_draw(s).finish() {
-            Ok((_, draw)) => Ok(draw),
-            Err(Error { input, code }) => Err(Error {
-                input: input.to_string(),
-                code,
-            }),
-        }
+        parse_all(parse_draw, s)
     }
 }
 
 #[aoc_generator(day2)]
-pub fn input_generator(input: &str) -> Vec<Game> {
-    input.lines().map(|s| Game::from_str(s).unwrap()).collect()
+pub fn input_generator(input: &str) -> Result<Vec<Game>, AocError> {
+    parse_lines(input)
 }
 
 #[aoc(day2, part1)]
-pub fn solve1(input: &[Game]) -> i32 {
+pub fn solve1(input: &[Game]) -> Result<i32, AocError> {
     let limits = &Draw {
         red: 12,
         green: 13,
         blue: 14,
     };
 
-    input.iter().fold(0, |id_sum, game| {
+    Ok(input.iter().fold(0, |id_sum, game| {
         if game.valid(limits) {
             id_sum + game.id as i32
         } else {
             id_sum
         }
-    })
+    }))
 }
 
 #[aoc(day2, part2)]
-pub fn solve2(input: &[Game]) -> i32 {
-    input.iter().fold(0, |power_sum, game| {
+pub fn solve2(input: &[Game]) -> Result<i32, AocError> {
+    input.iter().try_fold(0i32, |power_sum, game| {
         let mut max_draw = Draw::new(0, 0, 0);
 
         game.draws.iter().for_each(|draw| {
@@ -143,10 +140,34 @@ pub fn solve2(input: &[Game]) -> i32 {
             }
         });
 
-        power_sum + (max_draw.red as i32 * max_draw.green as i32 * max_draw.blue as i32)
+        power_sum
+            .checked_add(max_draw.red as i32 * max_draw.green as i32 * max_draw.blue as i32)
+            .ok_or(AocError::Overflow("sum of game powers"))
     })
 }
 
+pub struct Day02;
+
+impl Solution for Day02 {
+    const DAY: u8 = 2;
+    const TITLE: &'static str = "Cube Conundrum";
+
+    type Input = Vec<Game>;
+    type Output = i32;
+
+    fn parse(input: &str) -> Result<Self::Input, AocError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Output, AocError> {
+        solve1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Option<Result<Self::Output, AocError>> {
+        Some(solve2(input))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -162,6 +183,43 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_parse_error_line() {
+        assert_eq!(
+            input_generator("Game 1: 3 blue, 4 red\nGame two: 1 blue"),
+            Err(AocError::parse(2, 6, "game id", "Game two: 1 blue"))
+        );
+    }
+
+    #[test]
+    fn test_parse_error_in_draw() {
+        assert_eq!(
+            Game::from_str("Game 3: 8 green, 6 blue; 5 purple"),
+            Err(AocError::parse(
+                1,
+                28,
+                "cube colour (red, green or blue)",
+                "Game 3: 8 green, 6 blue; 5 purple"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_repeated_colour() {
+        assert_eq!(
+            Game::from_str("Game 1: 200 red, 100 red"),
+            Err(AocError::parse(
+                1,
+                22,
+                "a colour not already drawn",
+                "Game 1: 200 red, 100 red"
+            ))
+        );
+
+        // the same colour again in a later draw is fine
+        assert!(Game::from_str("Game 1: 200 red; 100 red").is_ok());
+    }
+
     #[test]
     fn test_power_sum() {
         let games = vec![
@@ -177,6 +235,6 @@ mod tests {
             .flat_map(Game::from_str)
             .collect::<Vec<Game>>();
 
-        assert_eq!(solve2(&parsed_games), 2286);
+        assert_eq!(solve2(&parsed_games), Ok(2286));
     }
 }
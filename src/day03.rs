@@ -1,123 +1,178 @@
 use std::collections::HashSet;
 
-const LINE_LENGTH: usize = 140;
+use crate::error::AocError;
+use crate::grid::{Grid, Pos};
+use crate::solution::Solution;
+
+#[derive(Debug, PartialEq)]
+pub struct Schematic {
+    grid: Grid<char>,
+    numbers: Vec<Number>,
+    // index into `numbers` of the number covering each cell, if any
+    number_at: Grid<Option<usize>>,
+}
 
-#[aoc(day3, part1)]
-pub fn solve1(input: &str) -> i32 {
-    let mut sum = 0;
-    let mut current_number: String = String::new();
-    let mut is_part_number: bool = false;
+// a run of digits on a single row of the schematic
+#[derive(Debug, PartialEq)]
+struct Number {
+    value: i32,
+    row: usize,
+    start_col: usize,
+    len: usize,
+}
 
-    let chars = input.chars().enumerate().collect::<Vec<_>>();
+impl Number {
+    fn cells(&self) -> impl Iterator<Item = Pos> + '_ {
+        (self.start_col..self.start_col + self.len).map(|col| Pos::new(self.row, col))
+    }
+}
 
-    for &(i, c) in &chars {
-        if !c.is_digit(10) {
-            if is_part_number {
-                sum += current_number.parse::<i32>().unwrap()
+impl Schematic {
+    fn new(grid: Grid<char>) -> Result<Schematic, AocError> {
+        let mut numbers = Vec::new();
+
+        for (row, cells) in grid.rows().enumerate() {
+            let mut col = 0;
+
+            while col < cells.len() {
+                let len = cells[col..]
+                    .iter()
+                    .take_while(|c| c.is_ascii_digit())
+                    .count();
+
+                if len > 0 {
+                    let value = cells[col..col + len]
+                        .iter()
+                        .collect::<String>()
+                        .parse()
+                        .map_err(|_| AocError::Overflow("part number"))?;
+
+                    numbers.push(Number {
+                        value,
+                        row,
+                        start_col: col,
+                        len,
+                    });
+                }
+
+                col += len.max(1);
             }
+        }
 
-            current_number = String::new();
-            is_part_number = false;
-            continue;
-        } else {
-            current_number.push(c);
+        let mut number_at = grid.map(|_| None);
 
-            if neighbours(&chars, i, LINE_LENGTH)
-                .iter()
-                .any(|&(_, c)| is_symbol(c))
-            {
-                is_part_number = true;
+        for (i, number) in numbers.iter().enumerate() {
+            for pos in number.cells() {
+                number_at[pos] = Some(i);
             }
         }
+
+        Ok(Schematic {
+            grid,
+            numbers,
+            number_at,
+        })
     }
 
-    sum
-}
+    fn is_part_number(&self, number: &Number) -> bool {
+        number
+            .cells()
+            .flat_map(|pos| self.grid.neighbours8(pos))
+            .any(|pos| is_symbol(self.grid[pos]))
+    }
 
-#[aoc(day3, part2)]
-pub fn solve2(input: &str) -> i32 {
-    let mut sum = 0;
+    // distinct numbers touching `pos`, including diagonally
+    fn adjacent_numbers(&self, pos: Pos) -> HashSet<usize> {
+        self.grid
+            .neighbours8(pos)
+            .filter_map(|p| self.number_at[p])
+            .collect()
+    }
+}
 
-    let chars = input.chars().enumerate().collect::<Vec<_>>();
+#[aoc_generator(day3)]
+pub fn input_generator(input: &str) -> Result<Schematic, AocError> {
+    Schematic::new(Grid::parse(input)?)
+}
 
-    for &(i, c) in &chars {
-        if c == '*' {
-            let ns = neighbours(&chars, i, LINE_LENGTH);
+#[aoc(day3, part1)]
+pub fn solve1(schematic: &Schematic) -> Result<i32, AocError> {
+    schematic
+        .numbers
+        .iter()
+        .filter(|number| schematic.is_part_number(number))
+        .try_fold(0i32, |sum, number| sum.checked_add(number.value))
+        .ok_or(AocError::Overflow("sum of part numbers"))
+}
 
-            let numeric_neighbour_cells = ns
-                .iter()
-                .filter(|(_, c)| c.is_digit(10))
-                .collect::<Vec<_>>();
+#[aoc(day3, part2)]
+pub fn solve2(schematic: &Schematic) -> Result<i32, AocError> {
+    let mut sum: i32 = 0;
 
-            let mut nums = HashSet::new();
+    for pos in schematic.grid.positions() {
+        if schematic.grid[pos] != '*' {
+            continue;
+        }
 
-            for &(num_idx, _) in numeric_neighbour_cells {
-                let num = read_number_at(&chars, num_idx).unwrap();
-                nums.insert(num);
-            }
+        let numbers = schematic.adjacent_numbers(pos);
 
-            if nums.len() == 2 {
-                sum += nums.iter().product::<i32>();
-            }
+        if numbers.len() == 2 {
+            sum = numbers
+                .iter()
+                .try_fold(1i32, |product, &i| {
+                    product.checked_mul(schematic.numbers[i].value)
+                })
+                .and_then(|ratio| sum.checked_add(ratio))
+                .ok_or(AocError::Overflow("sum of gear ratios"))?;
         }
     }
 
-    sum
+    Ok(sum)
 }
 
-fn read_number_at(indexed_chars: &[(usize, char)], num_idx: usize) -> Option<i32> {
-    let mut num_str = String::new();
-    let mut i = num_idx;
+fn is_symbol(c: char) -> bool {
+    c != '.' && !c.is_ascii_digit()
+}
 
-    loop {
-        if i == 0 || !indexed_chars[i - 1].1.is_digit(10) {
-            break;
-        }
-        i -= 1;
+pub struct Day03;
+
+impl Solution for Day03 {
+    const DAY: u8 = 3;
+    const TITLE: &'static str = "Gear Ratios";
+
+    type Input = Schematic;
+    type Output = i32;
+
+    fn parse(input: &str) -> Result<Self::Input, AocError> {
+        input_generator(input)
     }
 
-    loop {
-        if !indexed_chars[i].1.is_digit(10) {
-            break;
-        }
-        num_str.push(indexed_chars[i].1);
-        i += 1
+    fn part1(input: &Self::Input) -> Result<Self::Output, AocError> {
+        solve1(input)
     }
 
-    num_str.parse().ok()
+    fn part2(input: &Self::Input) -> Option<Result<Self::Output, AocError>> {
+        Some(solve2(input))
+    }
 }
 
-fn is_symbol(c: char) -> bool {
-    c != '.' && !c.is_digit(10) && c != '\n'
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-fn neighbours(
-    indexed_chars: &[(usize, char)],
-    index_to_check: usize,
-    line_length: usize,
-) -> Vec<(usize, char)> {
-    let mut result = vec![];
-
-    for y in 0i32..3 {
-        for x in 0i32..3 {
-            let idx = (index_to_check as i32 + x - 1) + ((y - 1) * (line_length + 1) as i32);
-
-            indexed_chars
-                .get(clamp(0, indexed_chars.len() as i32, idx) as usize)
-                .into_iter()
-                .for_each(|&(i, c)| result.push((i, c)));
-        }
+    #[test]
+    fn test_numbers_at_row_edges() {
+        // 12 ends one row and 3 starts the next, but they aren't neighbours
+        let schematic = input_generator("..12\n3...\n*...").unwrap();
+
+        assert_eq!(solve1(&schematic), Ok(3));
+        assert_eq!(schematic.adjacent_numbers(Pos::new(2, 0)).len(), 1);
     }
 
-    result
-}
+    #[test]
+    fn test_gear_with_equal_numbers() {
+        let schematic = input_generator("12.\n.*.\n12.").unwrap();
 
-fn clamp<T: PartialOrd>(min: T, max: T, x: T) -> T {
-    return if x < min {
-        min
-    } else if x > max {
-        max
-    } else {
-        x
-    };
+        assert_eq!(solve2(&schematic), Ok(144));
+    }
 }
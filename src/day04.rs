@@ -3,13 +3,14 @@ use std::collections::{HashSet, VecDeque};
 use std::str::FromStr;
 
 use nom::bytes::complete::tag;
-use nom::character::complete::digit1;
 use nom::character::complete::space1;
-use nom::combinator::map_res;
-use nom::error::Error;
-use nom::multi::separated_list0;
+use nom::combinator::eof;
+use nom::error::context;
 use nom::sequence::terminated;
-use nom::{Finish, IResult};
+
+use crate::error::AocError;
+use crate::parsing::{numbers, parse_all, parse_lines, unsigned, ParseResult};
+use crate::solution::Solution;
 
 #[derive(Debug, PartialEq)]
 pub struct Card {
@@ -24,71 +25,96 @@ impl Card {
     }
 }
 
-fn parse_card(s: &str) -> IResult<&str, Card> {
-    let (s, _) = tag("Card")(s)?;
+// e.g. "Card   1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53"
+fn parse_card(s: &str) -> ParseResult<'_, Card> {
+    let (s, _) = context("\"Card\"", tag("Card"))(s)?;
     let (s, _) = space1(s)?;
-    let (s, card_id) = map_res(digit1, u8::from_str)(s)?;
-    let (s, _) = tag(":")(s)?;
+    let (s, card_id) = context("card id", unsigned)(s)?;
+    let (s, _) = context("\":\"", tag(":"))(s)?;
     let (s, _) = space1(s)?;
-    let (s, winning) = terminated(separated_list0(space1, digit1), tag(" |"))(s)?;
+    let (s, winning) = terminated(numbers, context("a winning number or \" |\"", tag(" |")))(s)?;
     let (s, _) = space1(s)?;
-    let (s, chosen) = separated_list0(space1, digit1)(s)?;
+    let (s, chosen) = numbers(s)?;
+    let (s, _) = context("a chosen number or end of line", eof)(s)?;
 
     let card = Card {
         id: card_id,
-        winning: HashSet::from_iter(winning.into_iter().map(|s| u8::from_str(s).unwrap())),
-        chosen: HashSet::from_iter(chosen.into_iter().map(|s| u8::from_str(s).unwrap())),
+        winning: HashSet::from_iter(winning),
+        chosen: HashSet::from_iter(chosen),
     };
 
     Ok((s, card))
 }
 
 impl FromStr for Card {
-    type Err = Error<String>;
+    type Err = AocError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match parse_card(s).finish() {
-            Ok((_, card)) => Ok(card),
-            Err(Error { input, code }) => Err(Error {
-                input: input.to_string(),
-                code,
-            }),
-        }
+        parse_all(parse_card, s)
     }
 }
 
 #[aoc_generator(day4)]
-pub fn input_generator(input: &str) -> Vec<Card> {
-    input.lines().map(|s| Card::from_str(s).unwrap()).collect()
+pub fn input_generator(input: &str) -> Result<Vec<Card>, AocError> {
+    parse_lines(input)
 }
 
 #[aoc(day4, part1)]
-pub fn solve1(input: &[Card]) -> i32 {
-    input.iter().fold(0i32, |sum, card| {
-        sum + 2i32.pow(card.chosen_winning_numbers().len() as u32 - 1)
+pub fn solve1(input: &[Card]) -> Result<i32, AocError> {
+    input.iter().try_fold(0i32, |sum, card| {
+        // a card with no winning numbers is worth nothing, otherwise 1 point doubled for every
+        // winning number after the first
+        let points = match card.chosen_winning_numbers().len() as u32 {
+            0 => Some(0),
+            n => 2i32.checked_pow(n - 1),
+        };
+
+        points
+            .and_then(|p| sum.checked_add(p))
+            .ok_or(AocError::Overflow("total points"))
     })
 }
 
 #[aoc(day4, part2)]
-pub fn solve2(input: &[Card]) -> i32 {
+pub fn solve2(input: &[Card]) -> Result<i32, AocError> {
     let mut processed: Vec<&Card> = Vec::new();
-    let mut to_process: VecDeque<&Card> = VecDeque::from_iter(input.iter());
+    let mut to_process: VecDeque<usize> = (0..input.len()).collect();
 
-    while !to_process.is_empty() {
-        let card = to_process.pop_front().unwrap();
+    // cards win copies of the cards below them in the input, whatever their ids say
+    while let Some(position) = to_process.pop_front() {
+        let card = &input[position];
         let num_wins = card.chosen_winning_numbers().len();
 
-        let start = card.id as usize;
-        let end = min(card.id as usize + num_wins, input.len());
-
-        input[start..end]
-            .iter()
-            .for_each(|card| to_process.push_back(card));
+        let start = position + 1;
+        let end = min(start + num_wins, input.len());
 
+        to_process.extend(start..end);
         processed.push(card);
     }
 
-    processed.len() as i32
+    i32::try_from(processed.len()).map_err(|_| AocError::Overflow("number of scratchcards"))
+}
+
+pub struct Day04;
+
+impl Solution for Day04 {
+    const DAY: u8 = 4;
+    const TITLE: &'static str = "Scratchcards";
+
+    type Input = Vec<Card>;
+    type Output = i32;
+
+    fn parse(input: &str) -> Result<Self::Input, AocError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Output, AocError> {
+        solve1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Option<Result<Self::Output, AocError>> {
+        Some(solve2(input))
+    }
 }
 
 #[cfg(test)]
@@ -106,4 +132,30 @@ mod tests {
             })
         )
     }
+
+    #[test]
+    fn test_parse_card_error() {
+        assert_eq!(
+            input_generator("Card 1: 41 48 | 83 86\nCard 2: 13 3x | 61 30"),
+            Err(AocError::parse(
+                2,
+                13,
+                "a winning number or \" |\"",
+                "Card 2: 13 3x | 61 30"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_solve2_ids_out_of_order() {
+        // copies go to the cards below, so neither id 5 nor id 9 is out of range
+        assert_eq!(
+            solve2(&input_generator("Card 5: 1 2 | 1 3").unwrap()),
+            Ok(1)
+        );
+        assert_eq!(
+            solve2(&input_generator("Card 9: 1 | 1\nCard 3: 2 | 5").unwrap()),
+            Ok(3)
+        );
+    }
 }
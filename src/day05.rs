@@ -1,166 +1,1331 @@
-use std::iter;
+use std::cmp::{max, min};
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::fmt::{self, Display};
+use std::ops::Range;
 use std::str::FromStr;
 
-use nom::bytes::complete::{tag, take_till};
-use nom::character::complete::{digit1, line_ending, space1};
-use nom::multi::{many1, separated_list1};
-use nom::sequence::{preceded, terminated};
-use nom::IResult;
+use nom::branch::alt;
+use nom::bytes::complete::tag;
+use nom::character::complete::{alphanumeric1, line_ending, space1};
+use nom::combinator::{cut, eof, map, opt, peek};
+use nom::error::context;
+use nom::multi::separated_list1;
+use nom::sequence::{pair, preceded, separated_pair, terminated, tuple};
+
+use crate::error::{non_empty, AocError};
+use crate::parsing::{blocks, labelled, numbers, unsigned, ParseResult};
+use crate::solution::Solution;
+
+/// The category the seeds on the "seeds:" line belong to.
+pub const SEED: &str = "seed";
+/// The category the puzzle asks about.
+pub const LOCATION: &str = "location";
 
 #[derive(Debug, PartialEq)]
 pub struct Almanac {
-    seeds: Vec<u32>,
-    mappings: Vec<Vec<Mapping>>,
+    seeds: Vec<u64>,
+    // the "seeds:" line as written and its line in the input, to point into for part 2
+    seeds_text: String,
+    seeds_line: usize,
+    layers: Vec<Layer>,
+}
+
+/// One "<source>-to-<dest> map:" section.
+#[derive(Debug, PartialEq)]
+pub struct Layer {
+    source: String,
+    dest: String,
+    mappings: Vec<Mapping>,
+    // line of the header in the input, the rows follow it directly
+    line: usize,
 }
 
 #[derive(Debug, PartialEq)]
 pub struct Mapping {
-    source_start: u32,
-    dest_start: u32,
-    range_length: u32,
+    source_start: u64,
+    dest_start: u64,
+    range_length: u64,
+}
+
+/// The layers leading from one category to another, in the order they apply, see
+/// [`Almanac::route`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Route<'a> {
+    layers: Vec<&'a Layer>,
+}
+
+// One layer of a single id's way along a route: the mapping that moved it, if any.
+struct Step<'a> {
+    layer: &'a Layer,
+    mapping: Option<usize>,
+    from: u64,
+    to: u64,
+}
+
+/// A range of source ids that stay next to each other on their way along a route, with `dest`
+/// being where the first of them ends up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Piece {
+    pub source: Range<u64>,
+    pub dest: u64,
+}
+
+/// Every layer of a route composed into a single function: sorted, contiguous pieces covering
+/// all source ids, see [`Route::compose`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PiecewiseMap(Vec<Piece>);
+
+/// How much a validation issue matters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Worth knowing, but normal in puzzle inputs.
+    Note,
+    /// Probably a mistake, though the answers are still well defined.
+    Warning,
+    /// Some ids don't map the way the rows say they do.
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// The row moves some of the same ids as the earlier row on `line`, which wins.
+    Overlap { line: usize, ids: Range<u64> },
+    /// The row's source or destination range runs past the last 64-bit id, so it stops short.
+    Overflow,
+    /// The row has a range length of 0 and moves nothing.
+    ZeroLength,
+    /// The map has no rows at all.
+    EmptyLayer,
+    /// No row moves these ids, so they keep their numbers.
+    Gap { ids: Range<u64> },
+}
+
+/// Something [`Almanac::validate`] found, at a line of the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub line: usize,
+    /// e.g. "seed-to-soil"
+    pub map: String,
+    pub problem: Problem,
 }
 
 impl Almanac {
-    fn seed_location(&self, seed: u32) -> u32 {
-        self.mappings.iter().fold(seed, |id, mappings| {
-            mappings
+    /// Lints every map: rows whose source ranges overlap, rows with ranges that overflow or
+    /// are empty, maps without rows and ids that no row moves. Sorted by line.
+    pub fn validate(&self) -> Vec<Issue> {
+        let mut issues = Vec::new();
+
+        for layer in &self.layers {
+            let mut issue = |line, problem| {
+                issues.push(Issue {
+                    line,
+                    map: format!("{}-to-{}", layer.source, layer.dest),
+                    problem,
+                })
+            };
+
+            if layer.mappings.is_empty() {
+                issue(layer.line, Problem::EmptyLayer);
+                continue;
+            }
+
+            let mut rows = Vec::with_capacity(layer.mappings.len());
+
+            for (line, mapping) in (layer.line + 1..).zip(&layer.mappings) {
+                if mapping.range_length == 0 {
+                    issue(line, Problem::ZeroLength);
+                    continue;
+                }
+                if mapping.len() < mapping.range_length {
+                    issue(line, Problem::Overflow);
+                }
+
+                rows.push((line, mapping.source_range()));
+            }
+
+            rows.sort_by_key(|(_, range)| range.start);
+
+            // after sorting, a row can only overlap the rows before it that reach past its start
+            for (i, (line, range)) in rows.iter().enumerate() {
+                for (other, earlier) in &rows[..i] {
+                    if earlier.end > range.start {
+                        let ids = range.start..min(range.end, earlier.end);
+                        let (first, second) = (min(line, other), max(line, other));
+
+                        issue(*second, Problem::Overlap { line: *first, ids });
+                    }
+                }
+            }
+
+            let mut end = rows.first().map_or(0, |(_, range)| range.start);
+
+            for (_, range) in &rows {
+                if range.start > end {
+                    issue(
+                        layer.line,
+                        Problem::Gap {
+                            ids: end..range.start,
+                        },
+                    );
+                }
+
+                end = max(end, range.end);
+            }
+        }
+
+        issues.sort_by_key(|issue| issue.line);
+        issues
+    }
+
+    /// Every category that some map reads from or writes to.
+    pub fn categories(&self) -> BTreeSet<&str> {
+        self.layers
+            .iter()
+            .flat_map(|layer| [layer.source.as_str(), layer.dest.as_str()])
+            .collect()
+    }
+
+    /// Finds the shortest chain of maps from category `from` to category `to`, whatever order
+    /// the maps come in. Fails if either category isn't in any map, or if no chain connects
+    /// them.
+    pub fn route(&self, from: &str, to: &str) -> Result<Route<'_>, AocError> {
+        let categories = self.categories();
+
+        for category in [from, to] {
+            if !categories.contains(category) {
+                return Err(AocError::MissingCategory(category.to_string()));
+            }
+        }
+
+        let mut graph: HashMap<&str, Vec<&Layer>> = HashMap::new();
+
+        for layer in &self.layers {
+            graph.entry(&layer.source).or_default().push(layer);
+        }
+
+        // breadth-first, remembering the layer that first reached each category
+        let mut reached_by: HashMap<&str, Option<&Layer>> = HashMap::from([(from, None)]);
+        let mut queue = VecDeque::from([from]);
+
+        while let Some(category) = queue.pop_front() {
+            if category == to {
+                break;
+            }
+
+            for &layer in graph.get(category).into_iter().flatten() {
+                if !reached_by.contains_key(layer.dest.as_str()) {
+                    reached_by.insert(&layer.dest, Some(layer));
+                    queue.push_back(&layer.dest);
+                }
+            }
+        }
+
+        let mut layers = Vec::new();
+        let mut category = to;
+
+        loop {
+            match reached_by.get(category) {
+                None => {
+                    return Err(AocError::BrokenChain {
+                        from: from.to_string(),
+                        to: to.to_string(),
+                    })
+                }
+                Some(None) => break,
+                Some(Some(layer)) => {
+                    layers.push(*layer);
+                    category = &layer.source;
+                }
+            }
+        }
+
+        layers.reverse();
+
+        Ok(Route { layers })
+    }
+
+    /// Renders the almanac as a Graphviz DOT graph: a cluster per category holding the ranges
+    /// that maps read and write there, and an edge per mapping labelled with how far it moves
+    /// ids. Ids that no mapping in a layer covers go along a dashed "other ids" edge. Given a
+    /// seed, the edges it takes from seed to location are drawn in red and labelled with the
+    /// ids it has on the way, which fails if no chain of maps leads there.
+    pub fn to_dot(&self, seed: Option<u64>) -> Result<String, AocError> {
+        let route = seed.map(|_| self.route(SEED, LOCATION)).transpose()?;
+        let path = route
+            .as_ref()
+            .zip(seed)
+            .map_or_else(Vec::new, |(route, seed)| route.steps(seed));
+
+        let mut graph = DotGraph::default();
+
+        for layer in &self.layers {
+            let step = path.iter().find(|step| std::ptr::eq(step.layer, layer));
+
+            let rows = layer
+                .mappings
                 .iter()
-                .find_map(|mapping| mapping.translate(id))
-                .unwrap_or(id)
+                .enumerate()
+                .filter(|(_, mapping)| mapping.len() > 0)
+                .map(|(i, mapping)| {
+                    let offset = mapping.dest_start as i128 - mapping.source_start as i128;
+
+                    (
+                        Some(i),
+                        Some(mapping.source_range()),
+                        Some(mapping.dest_range()),
+                        format!("{:+}", offset),
+                    )
+                });
+
+            for (mapping, source, dest, label) in rows.chain([(None, None, None, "+0".into())]) {
+                let from = graph.node(&layer.source, source);
+                let to = graph.node(&layer.dest, dest);
+                let mut attributes = vec![];
+
+                match step.filter(|step| step.mapping == mapping) {
+                    Some(step) => {
+                        graph.highlighted.extend([from.clone(), to.clone()]);
+                        attributes.extend([
+                            format!("label=\"{} ({} → {})\"", label, step.from, step.to),
+                            "color=red".to_string(),
+                            "penwidth=2".to_string(),
+                        ])
+                    }
+                    None => attributes.push(format!("label=\"{}\"", label)),
+                }
+
+                if mapping.is_none() {
+                    attributes.push("style=dashed".to_string());
+                }
+
+                graph.edges.push(format!(
+                    "    {} -> {} [{}];",
+                    from,
+                    to,
+                    attributes.join(", ")
+                ));
+            }
+        }
+
+        Ok(graph.to_string())
+    }
+
+    // "seeds:" read as pairs of start and length, for part 2
+    fn seed_ranges(&self) -> Result<Vec<Range<u64>>, AocError> {
+        let pairs = self.seeds.chunks_exact(2);
+
+        if !pairs.remainder().is_empty() {
+            // the range length is missing from the end of the "seeds: " line
+            return Err(AocError::parse(
+                self.seeds_line,
+                self.seeds_text.chars().count() + 1,
+                "a seed range length",
+                &self.seeds_text,
+            ));
+        }
+
+        pairs
+            .map(|pair| {
+                let (start, size) = (pair[0], pair[1]);
+
+                start
+                    .checked_add(size)
+                    .map(|end| start..end)
+                    .ok_or(AocError::Overflow("end of seed range"))
+            })
+            .collect()
+    }
+}
+
+impl<'a> Route<'a> {
+    /// The categories along the route, from its start to its end. Empty for a route from a
+    /// category to itself.
+    pub fn categories(&self) -> Vec<&str> {
+        let mut categories = self
+            .layers
+            .iter()
+            .map(|layer| layer.source.as_str())
+            .collect::<Vec<_>>();
+
+        categories.extend(self.layers.last().map(|layer| layer.dest.as_str()));
+        categories
+    }
+
+    /// Looks a single id up by walking through every layer, see [`Route::compose`] for lots
+    /// of lookups.
+    pub fn get(&self, id: u64) -> u64 {
+        self.steps(id).last().map_or(id, |step| step.to)
+    }
+
+    // the way `id` takes through each layer
+    fn steps(&self, id: u64) -> Vec<Step<'a>> {
+        let mut steps = Vec::with_capacity(self.layers.len());
+        let mut from = id;
+
+        for &layer in &self.layers {
+            let found = layer
+                .mappings
+                .iter()
+                .enumerate()
+                .find_map(|(i, mapping)| mapping.translate(from).map(|to| (i, to)));
+
+            let step = Step {
+                layer,
+                mapping: found.map(|(i, _)| i),
+                from,
+                to: found.map_or(from, |(_, to)| to),
+            };
+
+            from = step.to;
+            steps.push(step);
+        }
+
+        steps
+    }
+
+    /// Pushes whole ranges of ids through every layer at once and returns the ranges they end
+    /// up in, in no particular order.
+    pub fn ranges(&self, ids: Vec<Range<u64>>) -> Vec<Range<u64>> {
+        let pieces = ids
+            .into_iter()
+            .map(|source| Piece {
+                dest: source.start,
+                source,
+            })
+            .collect();
+
+        self.push_through(pieces).iter().map(Piece::image).collect()
+    }
+
+    /// Composes all the layers into one map, so that looking up an id is a binary search
+    /// instead of a walk through every layer.
+    pub fn compose(&self) -> PiecewiseMap {
+        let all = Piece {
+            source: 0..u64::MAX,
+            dest: 0,
+        };
+
+        let mut pieces = self.push_through(vec![all]);
+        pieces.sort_by_key(|piece| piece.source.start);
+
+        // neighbours whose images ended up next to each other as well are really one piece
+        let merged = pieces
+            .into_iter()
+            .fold(Vec::<Piece>::new(), |mut merged, piece| {
+                match merged.last_mut() {
+                    Some(last)
+                        if last.source.end == piece.source.start
+                            && last.image().end == piece.dest =>
+                    {
+                        last.source.end = piece.source.end
+                    }
+                    _ => merged.push(piece),
+                }
+
+                merged
+            });
+
+        PiecewiseMap(merged)
+    }
+
+    /// Every id that ends up at `id`. Usually one, but nothing stops two mappings in a layer
+    /// from sending different ids to the same place.
+    pub fn sources_of(&self, id: u64) -> Vec<u64> {
+        self.sources(id..id.saturating_add(1))
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    /// The ids that end up somewhere in `ids`, found by going back through the layers from
+    /// the last to the first. The ranges come back sorted and merged.
+    pub fn sources(&self, ids: Range<u64>) -> Vec<Range<u64>> {
+        let sources = self.layers.iter().rev().fold(vec![ids], |ranges, layer| {
+            unmap_ranges(&layer.mappings, ranges)
+        });
+
+        merge(sources)
+    }
+
+    fn push_through(&self, pieces: Vec<Piece>) -> Vec<Piece> {
+        self.layers
+            .iter()
+            .fold(pieces, |pieces, layer| map_pieces(&layer.mappings, pieces))
+    }
+}
+
+// Splits every piece where its image crosses the boundaries of the layer's mappings and moves
+// the covered parts along. Whatever no mapping covers keeps its ids, same as for a single id.
+fn map_pieces(layer: &[Mapping], pieces: Vec<Piece>) -> Vec<Piece> {
+    let mut mapped = Vec::with_capacity(pieces.len());
+    let mut unmapped = pieces;
+
+    for mapping in layer {
+        let source = mapping.source_range();
+        let mut rest = Vec::with_capacity(unmapped.len());
+
+        for piece in unmapped {
+            let image = piece.image();
+            let overlap = max(image.start, source.start)..min(image.end, source.end);
+
+            if overlap.is_empty() {
+                rest.push(piece);
+                continue;
+            }
+
+            if image.start < overlap.start {
+                rest.push(piece.restrict(image.start..overlap.start));
+            }
+            if overlap.end < image.end {
+                rest.push(piece.restrict(overlap.end..image.end));
+            }
+
+            let mut moved = piece.restrict(overlap);
+            moved.dest = mapping.translate_from(moved.dest);
+            mapped.push(moved);
+        }
+
+        unmapped = rest;
+    }
+
+    mapped.extend(unmapped);
+    mapped
+}
+
+// The ids that the layer sends into `ranges`: whatever a mapping moves there, plus the parts of
+// `ranges` that no mapping covers, which stay where they are. Where rows overlap only the earlier
+// one applies, the same as going forwards, so a row's sources lose whatever earlier rows cover.
+fn unmap_ranges(layer: &[Mapping], ranges: Vec<Range<u64>>) -> Vec<Range<u64>> {
+    let mut sources = Vec::with_capacity(ranges.len());
+
+    for range in ranges {
+        for (i, mapping) in layer.iter().enumerate() {
+            let dest = mapping.dest_range();
+            let overlap = max(range.start, dest.start)..min(range.end, dest.end);
+
+            if !overlap.is_empty() {
+                let moved = mapping.untranslate(overlap.start)..mapping.untranslate(overlap.end);
+
+                sources.extend(without_sources(&layer[..i], moved));
+            }
+        }
+
+        sources.extend(without_sources(layer, range));
+    }
+
+    sources
+}
+
+// the parts of `range` that none of `mappings` takes as a source
+fn without_sources(mappings: &[Mapping], range: Range<u64>) -> Vec<Range<u64>> {
+    mappings.iter().fold(vec![range], |uncovered, mapping| {
+        let source = mapping.source_range();
+
+        uncovered
+            .into_iter()
+            .flat_map(|r| {
+                [
+                    r.start..min(r.end, source.start),
+                    max(r.start, source.end)..r.end,
+                ]
+            })
+            .filter(|r| !r.is_empty())
+            .collect()
+    })
+}
+
+// sorts the ranges and joins any that overlap or touch
+fn merge(mut ranges: Vec<Range<u64>>) -> Vec<Range<u64>> {
+    ranges.sort_by_key(|r| r.start);
+
+    ranges
+        .into_iter()
+        .fold(Vec::<Range<u64>>::new(), |mut merged, range| {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = max(last.end, range.end),
+                _ => merged.push(range),
+            }
+
+            merged
         })
+}
+
+impl Piece {
+    /// Where `id` ends up, for ids in `source`.
+    pub fn get(&self, id: u64) -> u64 {
+        self.dest + (id - self.source.start)
+    }
+
+    /// Where the piece's source ids end up.
+    pub fn image(&self) -> Range<u64> {
+        self.dest..self.dest + (self.source.end - self.source.start)
+    }
+
+    // the part of this piece whose image is `image`, which has to lie within `self.image()`
+    fn restrict(&self, image: Range<u64>) -> Piece {
+        let source = |id: u64| self.source.start + (id - self.dest);
+
+        Piece {
+            source: source(image.start)..source(image.end),
+            dest: image.start,
+        }
+    }
+}
+
+impl Problem {
+    pub fn severity(&self) -> Severity {
+        match self {
+            Problem::Overlap { .. } | Problem::Overflow => Severity::Error,
+            Problem::ZeroLength | Problem::EmptyLayer => Severity::Warning,
+            Problem::Gap { .. } => Severity::Note,
+        }
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Note => write!(f, "note"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+// e.g. "line 5: error: seed-to-soil map row overlaps line 4 at 98..100"
+impl Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}: {}: {} map ",
+            self.line,
+            self.problem.severity(),
+            self.map
+        )?;
+
+        match &self.problem {
+            Problem::Overlap { line, ids } => write!(
+                f,
+                "row overlaps line {} at {}..{}, only the earlier row applies",
+                line, ids.start, ids.end
+            ),
+            Problem::Overflow => write!(f, "row runs past the last 64-bit id"),
+            Problem::ZeroLength => write!(f, "row has a range length of 0"),
+            Problem::EmptyLayer => write!(f, "has no rows"),
+            Problem::Gap { ids } => write!(
+                f,
+                "leaves {}..{} unmapped between its rows",
+                ids.start, ids.end
+            ),
+        }
+    }
+}
+
+// the clusters and edges of `Almanac::to_dot`
+#[derive(Default)]
+struct DotGraph {
+    // categories in the order the maps mention them, each with its nodes and their attributes
+    clusters: Vec<(String, Vec<(String, String)>)>,
+    edges: Vec<String>,
+    // nodes on the highlighted seed's path
+    highlighted: Vec<String>,
+}
+
+impl DotGraph {
+    // the name of the node for `range` in `category`'s cluster, or for the ids no mapping covers
+    fn node(&mut self, category: &str, range: Option<Range<u64>>) -> String {
+        let (name, attributes) = match range {
+            Some(r) => (
+                format!("\"{} {}..{}\"", category, r.start, r.end),
+                format!("label=\"{}..{}\"", r.start, r.end),
+            ),
+            None => (
+                format!("\"{} other\"", category),
+                "label=\"other ids\", style=dashed".to_string(),
+            ),
+        };
+
+        let i = match self.clusters.iter().position(|(c, _)| c == category) {
+            Some(i) => i,
+            None => {
+                self.clusters.push((category.to_string(), Vec::new()));
+                self.clusters.len() - 1
+            }
+        };
+
+        let nodes = &mut self.clusters[i].1;
+
+        if !nodes.iter().any(|(n, _)| *n == name) {
+            nodes.push((name.clone(), attributes));
+        }
+
+        name
+    }
+}
+
+impl Display for DotGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "digraph almanac {{")?;
+        writeln!(f, "    rankdir=LR;")?;
+        writeln!(f, "    node [shape=box];")?;
+
+        for (category, nodes) in &self.clusters {
+            writeln!(f)?;
+            writeln!(f, "    subgraph cluster_{} {{", category)?;
+            writeln!(f, "        label=\"{}\";", category)?;
+
+            for (name, attributes) in nodes {
+                let colour = if self.highlighted.contains(name) {
+                    ", color=red"
+                } else {
+                    ""
+                };
+
+                writeln!(f, "        {} [{}{}];", name, attributes, colour)?;
+            }
+
+            writeln!(f, "    }}")?;
+        }
+
+        writeln!(f)?;
+
+        for edge in &self.edges {
+            writeln!(f, "{}", edge)?;
+        }
+
+        writeln!(f, "}}")
+    }
+}
+
+impl PiecewiseMap {
+    pub fn get(&self, id: u64) -> u64 {
+        let i = self.0.partition_point(|piece| piece.source.end <= id);
+
+        match self.0.get(i) {
+            Some(piece) if piece.source.contains(&id) => piece.get(id),
+            // only u64::MAX, which no half-open range can reach
+            _ => id,
+        }
+    }
+
+    pub fn pieces(&self) -> &[Piece] {
+        &self.0
+    }
+
+    /// The ids where the route is discontinuous, i.e. where each piece after the first starts.
+    pub fn breakpoints(&self) -> impl Iterator<Item = u64> + '_ {
+        self.0.iter().skip(1).map(|piece| piece.source.start)
     }
 }
 
 impl Mapping {
-    fn covers(&self, id: u32) -> bool {
-        id >= self.source_start && id <= self.source_start + self.range_length
+    // The number of ids the mapping actually moves. A range running past the last id, on either
+    // side, stops there, so everything below can add to the starts without overflowing.
+    fn len(&self) -> u64 {
+        self.range_length
+            .min(u64::MAX - self.source_start)
+            .min(u64::MAX - self.dest_start)
+    }
+
+    fn covers(&self, id: u64) -> bool {
+        self.source_range().contains(&id)
     }
 
-    fn translate(&self, id: u32) -> Option<u32> {
+    fn translate(&self, id: u64) -> Option<u64> {
         if self.covers(id) {
-            Some(((self.dest_start as i32 - self.source_start as i32) + id as i32) as u32)
+            Some(self.translate_from(id))
         } else {
             None
         }
     }
+
+    // `translate` for ids known to be in `source_range()`
+    fn translate_from(&self, id: u64) -> u64 {
+        self.dest_start + (id - self.source_start)
+    }
+
+    // the id that `translate` turns into `id`, for ids in `dest_range()`
+    fn untranslate(&self, id: u64) -> u64 {
+        self.source_start + (id - self.dest_start)
+    }
+
+    // the ids this mapping moves, as a half-open range
+    fn source_range(&self) -> Range<u64> {
+        self.source_start..self.source_start + self.len()
+    }
+
+    fn dest_range(&self) -> Range<u64> {
+        self.dest_start..self.dest_start + self.len()
+    }
 }
 
-fn parse_almanac(s: &str) -> IResult<&str, Almanac> {
-    let digits = |s| separated_list1(space1, digit1)(s);
+// e.g. "50 98 2", which is destination start, source start and range length. Once a line starts
+// with a number the rest of the row has to be there too.
+fn parse_mapping(s: &str) -> ParseResult<'_, Mapping> {
+    let row = tuple((
+        unsigned,
+        cut(preceded(space1, unsigned)),
+        cut(preceded(space1, unsigned)),
+        cut(context("end of mapping row", peek(alt((line_ending, eof))))),
+    ));
+
+    context(
+        "mapping row (destination, source and length)",
+        map(row, |(dest_start, source_start, range_length, _)| Mapping {
+            source_start,
+            dest_start,
+            range_length,
+        }),
+    )(s)
+}
+
+fn category(s: &str) -> ParseResult<'_, &str> {
+    context("a category name", alphanumeric1)(s)
+}
 
-    let mapping = preceded(
-        terminated(take_till(|c| c == ':'), tag(":\n")),
-        separated_list1(line_ending, digits),
+// e.g. "seed-to-soil map:" followed by its mapping rows
+fn parse_layer(s: &str) -> ParseResult<'_, Layer> {
+    let header = terminated(
+        separated_pair(category, context("\"-to-\"", tag("-to-")), category),
+        context("\" map:\"", tag(" map:")),
     );
 
-    let (s, _) = tag("seeds: ")(s)?;
-    let (s, seed_id_strings) = terminated(digits, line_ending)(s)?;
+    // a map without rows is allowed here so that `Almanac::validate` can point it out
+    let rows = opt(preceded(
+        line_ending,
+        cut(separated_list1(line_ending, parse_mapping)),
+    ));
 
-    let (s, mapping_strings) = many1(mapping)(s)?;
+    map(pair(header, rows), |((source, dest), mappings)| Layer {
+        source: source.to_string(),
+        dest: dest.to_string(),
+        mappings: mappings.unwrap_or_default(),
+        line: 1,
+    })(s)
+}
 
-    let seed_ids = seed_id_strings.into_iter().flat_map(|id| id.parse());
-    let mappings = mapping_strings.into_iter().map(|mappings| {
-        mappings
-            .into_iter()
-            .flat_map(|ids| match ids[..] {
-                [dst, src, len] => Ok(Mapping {
-                    source_start: u32::from_str(src).unwrap(),
-                    dest_start: u32::from_str(dst).unwrap(),
-                    range_length: u32::from_str(len).unwrap(),
-                }),
-                _ => Err("BooM"),
-            })
-            .collect::<Vec<_>>()
-    });
+// the seeds line and each map are separated by blank lines
+impl FromStr for Almanac {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let blocks = blocks(non_empty(s)?);
+        let (seeds, layers) = blocks.split_first().ok_or(AocError::EmptyInput)?;
 
-    Ok((
-        s,
-        Almanac {
-            seeds: seed_ids.collect(),
-            mappings: mappings.collect(),
-        },
-    ))
+        Ok(Almanac {
+            seeds: seeds.parse(labelled("seeds", context("seed number", numbers)))?,
+            seeds_text: seeds.text.to_string(),
+            seeds_line: seeds.first_line,
+            layers: layers
+                .iter()
+                .map(|block| {
+                    let layer = block.parse(parse_layer)?;
+
+                    Ok(Layer {
+                        line: block.first_line,
+                        ..layer
+                    })
+                })
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+#[aoc_generator(day5)]
+pub fn input_generator(input: &str) -> Result<Almanac, AocError> {
+    Almanac::from_str(non_empty(input)?)
 }
 
 #[aoc(day5, part1)]
-pub fn solve1(input: &str) -> u32 {
-    let (_, almanac) = parse_almanac(input).unwrap();
+pub fn solve1(almanac: &Almanac) -> Result<u64, AocError> {
+    let map = almanac.route(SEED, LOCATION)?.compose();
 
     almanac
         .seeds
         .iter()
-        .map(|&s| almanac.seed_location(s))
+        .map(|&s| map.get(s))
         .min()
-        .unwrap()
+        .ok_or(AocError::EmptyInput)
 }
 
 #[aoc(day5, part2)]
-pub fn solve2(input: &str) -> u32 {
-    let (_, almanac) = parse_almanac(input).unwrap();
+pub fn solve2(almanac: &Almanac) -> Result<u64, AocError> {
+    almanac
+        .route(SEED, LOCATION)?
+        .ranges(almanac.seed_ranges()?)
+        .into_iter()
+        .filter(|range| !range.is_empty())
+        .map(|range| range.start)
+        .min()
+        .ok_or(AocError::EmptyInput)
+}
 
-    let all_seeds = almanac.seeds.chunks(2).flat_map(|c| match *c {
-        [s, size] => {
-            let mut curr = s;
+pub struct Day05;
 
-            iter::from_fn(move || {
-                let tmp = curr;
-                curr += 1;
+impl Solution for Day05 {
+    const DAY: u8 = 5;
+    const TITLE: &'static str = "If You Give A Seed A Fertilizer";
 
-                if curr < s + size {
-                    Some(tmp)
-                } else {
-                    None
-                }
-            })
-        }
-        _ => panic!("a"),
-    });
+    type Input = Almanac;
+    type Output = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, AocError> {
+        input_generator(input)
+    }
 
-    all_seeds.map(|s| almanac.seed_location(s)).min().unwrap()
+    fn part1(input: &Self::Input) -> Result<Self::Output, AocError> {
+        solve1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Option<Result<Self::Output, AocError>> {
+        Some(solve2(input))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const SAMPLE: &str = "seeds: 79 14 55 13
+    const SAMPLE: &str = include_str!("../inputs/2023/day05.sample.txt");
 
-seed-to-soil map:
-50 98 2
-52 50 48
+    #[test]
+    fn test_example() {
+        let alm = Almanac::from_str(SAMPLE).unwrap();
+        let route = alm.route(SEED, LOCATION).unwrap();
+        assert_eq!(route.get(79), 82);
+        assert_eq!(route.get(14), 43);
+    }
 
-soil-to-fertilizer map:
-0 15 37
-37 52 2
-39 0 15
+    #[test]
+    fn test_boundary_ids() {
+        let alm = Almanac::from_str("seeds: 1\n\nseed-to-location map:\n50 98 2").unwrap();
+        let route = alm.route(SEED, LOCATION).unwrap();
 
-fertilizer-to-water map:
-49 53 8
-0 11 42
-42 0 7
-57 7 4
+        // the range is 98 and 99, so 100 is the first id past the end
+        assert_eq!(route.get(97), 97);
+        assert_eq!(route.get(98), 50);
+        assert_eq!(route.get(99), 51);
+        assert_eq!(route.get(100), 100);
+        assert_eq!(route.compose().get(100), 100);
+        assert_eq!(route.sources(50..52), vec![50..52, 98..100]);
+    }
 
-water-to-light map:
-88 18 7
-18 25 70
+    #[test]
+    fn test_values_past_u32() {
+        let max = u32::MAX as u64;
+        let input = format!(
+            "seeds: {} 10\n\nseed-to-location map:\n{} {} 10\n0 {} 5",
+            max - 5,
+            max + 100,
+            max - 5,
+            max + 5
+        );
+        let alm = Almanac::from_str(&input).unwrap();
+        let route = alm.route(SEED, LOCATION).unwrap();
 
-light-to-temperature map:
-45 77 23
-81 45 19
-68 64 13
+        assert_eq!(route.get(max - 5), max + 100);
+        assert_eq!(route.get(max), max + 105);
+        assert_eq!(route.get(max + 4), max + 109);
+        assert_eq!(route.get(max + 5), 0);
+        assert_eq!(solve1(&alm), Ok(10));
+        assert_eq!(solve2(&alm), Ok(max + 100));
+        assert_eq!(route.sources_of(max + 109), vec![max + 4, max + 109]);
+    }
 
-temperature-to-humidity map:
-0 69 1
-1 0 69
+    #[test]
+    fn test_ranges_stop_at_last_id() {
+        let max = u64::MAX;
+        let input = format!(
+            "seeds: 1\n\nseed-to-soil map:\n0 {} 10\n\nsoil-to-location map:\n{} 0 10",
+            max - 5,
+            max - 5
+        );
+        let alm = Almanac::from_str(&input).unwrap();
+        let route = alm.route(SEED, LOCATION).unwrap();
+        let map = route.compose();
 
-humidity-to-location map:
-60 56 37
-56 93 4";
+        // both maps run past u64::MAX, so they only move the 5 ids that exist on both sides
+        assert_eq!(route.get(max - 5), max - 5);
+        assert_eq!(route.get(max - 1), max - 1);
+        assert_eq!(route.get(4), max - 1);
+        assert_eq!(route.get(5), 5);
+        assert_eq!(map.get(max - 1), max - 1);
+        assert_eq!(map.get(4), max - 1);
+        assert_eq!(
+            route
+                .ranges(vec![0..10, 20..21])
+                .into_iter()
+                .min_by_key(|r| r.start),
+            Some(5..10)
+        );
+    }
 
     #[test]
-    fn test_example() {
-        let (_, alm) = parse_almanac(SAMPLE).unwrap();
-        assert_eq!(alm.seed_location(79), 82);
-        assert_eq!(alm.seed_location(14), 43);
+    fn test_route_between_any_categories() {
+        let alm = Almanac::from_str(SAMPLE).unwrap();
+        let route = alm.route("soil", "humidity").unwrap();
+
+        assert_eq!(
+            route.categories(),
+            vec![
+                "soil",
+                "fertilizer",
+                "water",
+                "light",
+                "temperature",
+                "humidity"
+            ]
+        );
+        // seed 79 is soil 81 and humidity 78
+        assert_eq!(route.get(81), 78);
+        assert_eq!(alm.route("water", "water").unwrap().get(5), 5);
+    }
+
+    #[test]
+    fn test_route_ignores_map_order() {
+        let blocks = SAMPLE.split("\n\n").collect::<Vec<_>>();
+        let (seeds, maps) = blocks.split_first().unwrap();
+        let shuffled = [*seeds]
+            .into_iter()
+            .chain(maps.iter().rev().copied())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        let alm = Almanac::from_str(&shuffled).unwrap();
+
+        assert_eq!(solve1(&alm), Ok(35));
+        assert_eq!(solve2(&alm), Ok(46));
+    }
+
+    #[test]
+    fn test_route_errors() {
+        let alm = Almanac::from_str(SAMPLE).unwrap();
+
+        assert_eq!(
+            alm.route(SEED, "weather").unwrap_err(),
+            AocError::MissingCategory("weather".to_string())
+        );
+        assert_eq!(
+            alm.route(LOCATION, SEED).unwrap_err(),
+            AocError::BrokenChain {
+                from: "location".to_string(),
+                to: "seed".to_string()
+            }
+        );
+
+        let broken = Almanac::from_str(&SAMPLE.replace("water-to-light", "water-to-lamp")).unwrap();
+
+        assert_eq!(
+            solve1(&broken),
+            Err(AocError::BrokenChain {
+                from: "seed".to_string(),
+                to: "location".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_error() {
+        assert_eq!(
+            input_generator(&SAMPLE.replacen("37 52 2", "37 52", 1)),
+            Err(AocError::parse(
+                9,
+                6,
+                "mapping row (destination, source and length)",
+                "37 52"
+            ))
+        );
+
+        assert_eq!(
+            input_generator(&SAMPLE.replacen("0 11 42", "0 11 42 7", 1)),
+            Err(AocError::parse(14, 8, "end of mapping row", "0 11 42 7"))
+        );
+
+        assert_eq!(
+            input_generator(&SAMPLE.replacen("soil-to-fertilizer", "soil to fertilizer", 1)),
+            Err(AocError::parse(7, 5, "\"-to-\"", "soil to fertilizer map:"))
+        );
+    }
+
+    #[test]
+    fn test_route_ranges() {
+        let alm = Almanac::from_str(SAMPLE).unwrap();
+        let route = alm.route(SEED, LOCATION).unwrap();
+        let seeds = alm.seed_ranges().unwrap();
+
+        // every seed has to come out at the same location as when looked up on its own
+        let mut by_range = route
+            .ranges(seeds.clone())
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        let mut by_seed = seeds
+            .into_iter()
+            .flatten()
+            .map(|seed| route.get(seed))
+            .collect::<Vec<_>>();
+
+        by_range.sort();
+        by_seed.sort();
+
+        assert_eq!(by_range, by_seed);
+    }
+
+    #[test]
+    fn test_map_pieces_splits_at_boundaries() {
+        let layer = [Mapping {
+            source_start: 10,
+            dest_start: 100,
+            range_length: 5,
+        }];
+        let piece = |source, dest| Piece { source, dest };
+
+        let mut pieces = map_pieces(&layer, vec![piece(0..12, 0), piece(3..10, 13)]);
+        pieces.sort_by_key(|p| p.source.start);
+
+        assert_eq!(
+            pieces,
+            vec![
+                piece(0..10, 0),
+                piece(3..5, 103),
+                piece(5..10, 15),
+                piece(10..12, 100)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compose() {
+        let alm = Almanac::from_str(SAMPLE).unwrap();
+        let route = alm.route(SEED, LOCATION).unwrap();
+        let map = route.compose();
+
+        for seed in 0..200 {
+            assert_eq!(map.get(seed), route.get(seed), "seed {}", seed);
+        }
+        assert_eq!(map.get(u64::MAX - 1), u64::MAX - 1);
+
+        // the pieces cover every seed, in order and without gaps
+        let pieces = map.pieces();
+        assert_eq!(pieces.first().map(|p| p.source.start), Some(0));
+        assert_eq!(pieces.last().map(|p| p.source.end), Some(u64::MAX));
+        assert!(pieces
+            .windows(2)
+            .all(|w| w[0].source.end == w[1].source.start));
+        assert!(pieces.windows(2).all(|w| w[0].image().end != w[1].dest));
+
+        assert_eq!(map.breakpoints().count(), pieces.len() - 1);
+        assert!(map.breakpoints().any(|seed| seed == 98));
+    }
+
+    #[test]
+    fn test_sources_of() {
+        let alm = Almanac::from_str(SAMPLE).unwrap();
+        let route = alm.route(SEED, LOCATION).unwrap();
+
+        for seed in 0..200 {
+            let location = route.get(seed);
+            let seeds = route.sources_of(location);
+
+            assert!(seeds.contains(&seed), "seed {} at {}", seed, location);
+            assert!(seeds.iter().all(|&s| route.get(s) == location));
+        }
+    }
+
+    #[test]
+    fn test_sources_cross_check_part2() {
+        let alm = Almanac::from_str(SAMPLE).unwrap();
+        let route = alm.route(SEED, LOCATION).unwrap();
+        let lowest = solve2(&alm).unwrap();
+        let planted = alm.seed_ranges().unwrap();
+        let overlaps = |seeds: &[Range<u64>]| {
+            seeds.iter().any(|s| {
+                planted
+                    .iter()
+                    .any(|p| max(s.start, p.start) < min(s.end, p.end))
+            })
+        };
+
+        // no planted seed gets anywhere below the answer, but one of them gets to it
+        assert!(!overlaps(&route.sources(0..lowest)));
+        assert!(overlaps(&route.sources(lowest..lowest + 1)));
+    }
+
+    #[test]
+    fn test_sources_overlapping_rows() {
+        // 5..10 is covered by both rows, and the first one wins
+        let alm =
+            Almanac::from_str("seeds: 1\n\nseed-to-location map:\n100 0 10\n200 5 10").unwrap();
+        let route = alm.route(SEED, LOCATION).unwrap();
+
+        assert_eq!(route.sources_of(202), vec![202]);
+        assert_eq!(route.sources_of(107), vec![7, 107]);
+        assert_eq!(route.sources(200..215), vec![10..15, 200..215]);
+
+        for id in (0..20).chain(95..120).chain(195..220) {
+            for seed in route.sources_of(id) {
+                assert_eq!(route.get(seed), id, "seed {} for {}", seed, id);
+            }
+        }
+
+        for seed in 0..220 {
+            assert!(route.sources_of(route.get(seed)).contains(&seed));
+        }
+    }
+
+    #[test]
+    fn test_sources_non_injective_layer() {
+        // 5..10 and 20..25 both go to 100..105, and 100..105 stays put as well
+        let alm =
+            Almanac::from_str("seeds: 1\n\nseed-to-location map:\n100 5 5\n100 20 5").unwrap();
+        let route = alm.route(SEED, LOCATION).unwrap();
+
+        assert_eq!(route.sources(98..102), vec![5..7, 20..22, 98..102]);
+        assert_eq!(route.sources_of(101), vec![6, 21, 101]);
+    }
+
+    #[test]
+    fn test_validate() {
+        let alm = Almanac::from_str(SAMPLE).unwrap();
+
+        assert!(alm
+            .validate()
+            .iter()
+            .all(|issue| issue.problem.severity() == Severity::Note));
+
+        let input = [
+            "seeds: 1 2",
+            "",
+            "seed-to-soil map:",
+            "50 98 2",
+            "52 50 48",
+            "10 90 10",
+            "0 0 0",
+            "",
+            "soil-to-water map:",
+            "",
+            "water-to-location map:",
+            "0 18446744073709551610 10",
+            "100 0 10",
+        ]
+        .join("\n");
+        let alm = Almanac::from_str(&input).unwrap();
+        let issues = alm.validate();
+        let problem = |line, map: &str, problem| Issue {
+            line,
+            map: map.to_string(),
+            problem,
+        };
+
+        assert_eq!(
+            issues,
+            vec![
+                problem(
+                    6,
+                    "seed-to-soil",
+                    Problem::Overlap {
+                        line: 5,
+                        ids: 90..98
+                    }
+                ),
+                problem(
+                    6,
+                    "seed-to-soil",
+                    Problem::Overlap {
+                        line: 4,
+                        ids: 98..100
+                    }
+                ),
+                problem(7, "seed-to-soil", Problem::ZeroLength),
+                problem(9, "soil-to-water", Problem::EmptyLayer),
+                problem(
+                    11,
+                    "water-to-location",
+                    Problem::Gap {
+                        ids: 10..18446744073709551610
+                    }
+                ),
+                problem(12, "water-to-location", Problem::Overflow),
+            ]
+        );
+        assert_eq!(
+            issues[1].to_string(),
+            "line 6: error: seed-to-soil map row overlaps line 4 at 98..100, \
+                only the earlier row applies"
+        );
+        assert_eq!(
+            issues[3].to_string(),
+            "line 9: warning: soil-to-water map has no rows"
+        );
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let alm = Almanac::from_str(
+            "seeds: 79\n\nseed-to-soil map:\n50 98 2\n52 50 48\n\nsoil-to-location map:\n0 15 37",
+        )
+        .unwrap();
+
+        let dot = alm.to_dot(None).unwrap();
+
+        assert!(dot.starts_with("digraph almanac {\n"));
+        assert!(dot.contains("    subgraph cluster_soil {\n        label=\"soil\";\n"));
+        assert!(dot.contains("        \"seed 98..100\" [label=\"98..100\"];\n"));
+        assert!(dot.contains("    \"seed 98..100\" -> \"soil 50..52\" [label=\"-48\"];\n"));
+        assert!(dot
+            .contains("    \"soil other\" -> \"location other\" [label=\"+0\", style=dashed];\n"));
+        assert!(!dot.contains("red"));
+
+        let dot = alm.to_dot(Some(79)).unwrap();
+
+        assert!(dot.contains(
+            "    \"seed 50..98\" -> \"soil 52..100\" \
+                [label=\"+2 (79 → 81)\", color=red, penwidth=2];\n"
+        ));
+        assert!(dot.contains("        \"soil 52..100\" [label=\"52..100\", color=red];\n"));
+        assert!(dot.contains(
+            "    \"soil other\" -> \"location other\" \
+                [label=\"+0 (81 → 81)\", color=red, penwidth=2, style=dashed];\n"
+        ));
+        assert_eq!(dot.matches("penwidth").count(), 2);
+
+        assert_eq!(
+            Almanac::from_str("seeds: 1\n\nseed-to-soil map:\n1 2 3")
+                .unwrap()
+                .to_dot(Some(1)),
+            Err(AocError::MissingCategory("location".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_odd_seed_count() {
+        let alm = input_generator(&SAMPLE.replacen("79 14 55 13", "79 14 55", 1)).unwrap();
+
+        assert_eq!(
+            solve2(&alm),
+            Err(AocError::parse(
+                1,
+                16,
+                "a seed range length",
+                "seeds: 79 14 55"
+            ))
+        );
+
+        // pointed at where the line really is, as it's written
+        let alm = input_generator(&format!(
+            "\n\n{}",
+            SAMPLE.replacen("79 14 55 13", "79  14 55", 1)
+        ))
+        .unwrap();
+
+        assert_eq!(
+            solve2(&alm),
+            Err(AocError::parse(
+                3,
+                17,
+                "a seed range length",
+                "seeds: 79  14 55"
+            ))
+        );
     }
 }
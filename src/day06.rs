@@ -1,9 +1,17 @@
-use nom::bytes::complete::tag;
-use nom::character::complete::{digit1, space1};
-use nom::combinator::map;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use nom::branch::alt;
+use nom::character::complete::{digit1, line_ending, multispace0, space0, space1};
+use nom::combinator::{consumed, eof, peek};
+use nom::error::{context, VerboseError, VerboseErrorKind};
 use nom::multi::separated_list1;
-use nom::sequence::pair;
-use nom::IResult;
+use nom::sequence::{pair, terminated, tuple};
+use nom::Offset;
+
+use crate::error::AocError;
+use crate::parsing::{labelled, parse_all, Located, ParseResult};
+use crate::solution::Solution;
 
 pub struct RaceLog {
     races: Vec<Race>,
@@ -15,73 +23,529 @@ pub struct Race {
 }
 
 impl Race {
+    pub fn new(time: u64, distance_record: u64) -> Race {
+        Race {
+            time,
+            distance_record,
+        }
+    }
+
+    /// How far the boat goes when the button is held for `hold` milliseconds. Holding for the
+    /// whole race or longer goes nowhere.
+    pub fn distance(&self, hold: u64) -> u128 {
+        hold as u128 * self.time.saturating_sub(hold) as u128
+    }
+
+    /// How much further than the record holding for `hold` goes, negative if it loses.
+    pub fn margin(&self, hold: u64) -> i128 {
+        self.distance(hold) as i128 - self.distance_record as i128
+    }
+
+    /// The hold time that goes furthest. For an odd race time the two middle hold times tie,
+    /// and this is the shorter one.
+    pub fn best_hold(&self) -> u64 {
+        self.time / 2
+    }
+
+    /// The shortest and longest hold times that beat the record, every hold time in between
+    /// beats it too. None if the record can't be beaten.
+    //
+    // Holding for h goes h * (time - h), so the winning hold times are the integers strictly
+    // between the roots of h² - time·h + record = 0, symmetric around time / 2. An integer
+    // square root of the discriminant lands within a step of the shortest one, and the longest
+    // follows by symmetry. u128 keeps time² exact for any u64 time.
+    pub fn winning_interval(&self) -> Option<RangeInclusive<u64>> {
+        let (time, record) = (self.time as u128, self.distance_record as u128);
+        let beats = |hold: u128| hold * (time - hold) > record;
+
+        // no real roots, or a single one where the best hold only ties the record
+        let discriminant = (time * time).checked_sub(4 * record)?;
+
+        let mut shortest = (time - discriminant.isqrt()) / 2;
+
+        while shortest > 0 && beats(shortest - 1) {
+            shortest -= 1;
+        }
+        while shortest <= time / 2 && !beats(shortest) {
+            shortest += 1;
+        }
+
+        if shortest > time / 2 {
+            None
+        } else {
+            Some(shortest as u64..=(time - shortest) as u64)
+        }
+    }
+
+    /// Every hold time that beats the record, shortest first, with the distance it goes.
+    pub fn winning_strategies(&self) -> impl Iterator<Item = (u64, u128)> + '_ {
+        self.winning_interval()
+            .into_iter()
+            .flatten()
+            .map(|hold| (hold, self.distance(hold)))
+    }
+
     fn num_record_breaks(&self) -> u64 {
-        let mut count = 0;
+        // holding for 0 never wins, so there are at most time - 1 winning hold times
+        self.winning_interval()
+            .map_or(0, |winning| winning.end() - winning.start() + 1)
+    }
 
-        for hold_time in 0..self.time + 1 {
-            if hold_time * (self.time - hold_time) > self.distance_record {
-                count += 1
-            }
+    #[cfg(test)]
+    fn num_record_breaks_brute_force(&self) -> u64 {
+        (0..=self.time)
+            .filter(|hold| hold * (self.time - hold) > self.distance_record)
+            .count() as u64
+    }
+}
+
+/// How to read the digits on the "Time:" and "Distance:" rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kerning {
+    /// Every group of digits is a race of its own, as in part 1.
+    Spaced,
+    /// The gaps between the digits are bad kerning, so each row is one race, as in part 2.
+    Joined,
+}
+
+impl Kerning {
+    // the numbers in a row's groups of digits, or the index of the group where one that doesn't
+    // fit in 64 bits starts
+    fn read(self, groups: &[&str]) -> Result<Vec<u64>, usize> {
+        match self {
+            Kerning::Spaced => groups
+                .iter()
+                .enumerate()
+                .map(|(i, group)| u64::from_str(group).map_err(|_| i))
+                .collect(),
+            Kerning::Joined => u64::from_str(&groups.concat())
+                .map(|n| vec![n])
+                .map_err(|_| 0),
         }
+    }
+}
 
-        count
+const TOO_BIG: &str = "a number that fits in 64 bits";
+
+// e.g. "Time:      7  15   30" as its groups of digits, up to but not including the line ending
+fn parse_row<'a>(
+    label: &'static str,
+    what: &'static str,
+) -> impl FnMut(&'a str) -> ParseResult<'a, Vec<&'a str>> {
+    let groups = separated_list1(space1, digit1);
+    let end = context("a number or end of line", peek(alt((line_ending, eof))));
+
+    labelled(label, terminated(context(what, groups), pair(space0, end)))
+}
+
+// the groups of digits on both rows, and the whole "Distance:" row
+fn parse_race_sheet(s: &str) -> ParseResult<'_, (Vec<&str>, &str, Vec<&str>)> {
+    let (s, _) = multispace0(s)?;
+    let (s, times) = parse_row("Time", "race times")(s)?;
+    let (s, _) = tuple((space0, line_ending, multispace0))(s)?;
+    let (s, (row, distances)) = consumed(parse_row("Distance", "record distances"))(s)?;
+
+    Ok((s, (times, row, distances)))
+}
+
+fn parse_race_log(input: &str, kerning: Kerning) -> ParseResult<'_, RaceLog> {
+    let (s, (times, distances_row, distances)) = parse_race_sheet(input)?;
+
+    let times = kerning
+        .read(&times)
+        .map_err(|i| failure(input, times[i], TOO_BIG))?;
+    let distances = kerning
+        .read(&distances)
+        .map_err(|i| failure(input, distances[i], TOO_BIG))?;
+
+    if distances.len() != times.len() {
+        return Err(failure(
+            input,
+            distances_row,
+            "as many record distances as race times",
+        ));
     }
+
+    Ok((s, RaceLog::new(times, distances)))
+}
+
+// a failure found once the rows are parsed, pointing at where `at` starts in `input`
+fn failure<'a>(
+    input: &'a str,
+    at: &str,
+    expected: &'static str,
+) -> nom::Err<VerboseError<&'a str>> {
+    nom::Err::Failure(VerboseError {
+        errors: vec![(
+            &input[input.offset(at)..],
+            VerboseErrorKind::Context(expected),
+        )],
+    })
 }
 
-fn parse_race_log_1(s: &str) -> IResult<&str, RaceLog> {
-    let nums = |s| separated_list1(space1, digit1)(s);
+/// The "Time:" and "Distance:" rows split into groups of digits, which is all the parsing the
+/// two parts have in common. Reading the groups as numbers depends on the [`Kerning`].
+pub struct RaceSheet {
+    times: Vec<Located>,
+    distances: Vec<Located>,
+    // the whole "Distance:" row, to point at if it lists a different number of races
+    distances_row: Located,
+}
+
+impl RaceSheet {
+    /// Extra spaces, blank lines and CRLF line endings are fine.
+    pub fn parse(input: &str) -> Result<RaceSheet, AocError> {
+        let (times, distances_row, distances) = parse_all(parse_race_sheet, input)?;
+        let located = |groups: Vec<&str>| {
+            groups
+                .into_iter()
+                .map(|group| Located::new(input, group))
+                .collect()
+        };
+
+        Ok(RaceSheet {
+            times: located(times),
+            distances: located(distances),
+            distances_row: Located::new(input, distances_row),
+        })
+    }
+
+    /// Reads the races with `kerning`, the same as [`RaceLog::parse`] would.
+    pub fn races(&self, kerning: Kerning) -> Result<RaceLog, AocError> {
+        let read = |row: &[Located]| {
+            let groups = row.iter().map(|g| g.text.as_str()).collect::<Vec<_>>();
+
+            kerning.read(&groups).map_err(|i| row[i].error(TOO_BIG))
+        };
+
+        let times = read(&self.times)?;
+        let distances = read(&self.distances)?;
 
-    let (s, _) = pair(tag("Time:"), space1)(s)?;
-    let (s, times) = nums(s)?;
-    let (s, _) = pair(tag("\nDistance:"), space1)(s)?;
-    let (s, dists) = nums(s)?;
+        if distances.len() != times.len() {
+            return Err(self
+                .distances_row
+                .error("as many record distances as race times"));
+        }
+
+        Ok(RaceLog::new(times, distances))
+    }
+}
 
-    let races = times.into_iter().zip(dists).map(|(a, b)| Race {
-        time: a.parse().unwrap(),
-        distance_record: b.parse().unwrap(),
-    });
+impl RaceLog {
+    // the rows already hold the same number of races
+    fn new(times: Vec<u64>, distances: Vec<u64>) -> RaceLog {
+        let races = times
+            .into_iter()
+            .zip(distances)
+            .map(|(time, distance_record)| Race {
+                time,
+                distance_record,
+            });
 
-    Ok((
-        s,
         RaceLog {
             races: races.collect(),
-        },
-    ))
-}
+        }
+    }
 
-fn parse_race_log_2(s: &str) -> IResult<&str, RaceLog> {
-    let nums = |s| map(separated_list1(space1, digit1), |strs| strs.join(""))(s);
+    /// Reads a "Time:" row and a "Distance:" row, which have to list the same number of races.
+    /// Extra spaces, blank lines and CRLF line endings are fine.
+    pub fn parse(input: &str, kerning: Kerning) -> Result<RaceLog, AocError> {
+        parse_all(|s| parse_race_log(s, kerning), input)
+    }
 
-    let (s, _) = pair(tag("Time:"), space1)(s)?;
-    let (s, times) = nums(s)?;
-    let (s, _) = pair(tag("\nDistance:"), space1)(s)?;
-    let (s, dists) = nums(s)?;
+    pub fn races(&self) -> &[Race] {
+        &self.races
+    }
 
-    Ok((
-        s,
-        RaceLog {
-            races: vec![Race {
-                time: times.parse().unwrap(),
-                distance_record: dists.parse().unwrap(),
-            }],
-        },
-    ))
+    fn ways_to_win(&self) -> Result<u64, AocError> {
+        self.races.iter().try_fold(1u64, |wins, race| {
+            wins.checked_mul(race.num_record_breaks())
+                .ok_or(AocError::Overflow("product of ways to win"))
+        })
+    }
 }
 
-#[aoc(day6, part1)]
-fn solve1(input: &str) -> u64 {
-    let (_, log) = parse_race_log_1(input).unwrap();
+#[aoc_generator(day6)]
+pub fn input_generator(input: &str) -> Result<RaceSheet, AocError> {
+    RaceSheet::parse(input)
+}
 
-    log.races
-        .iter()
-        .fold(1, |wins, race| wins * race.num_record_breaks())
+#[aoc(day6, part1)]
+pub fn solve1(input: &RaceSheet) -> Result<u64, AocError> {
+    input.races(Kerning::Spaced)?.ways_to_win()
 }
 
 #[aoc(day6, part2)]
-fn solve2(input: &str) -> u64 {
-    let (_, log) = parse_race_log_2(input).unwrap();
+pub fn solve2(input: &RaceSheet) -> Result<u64, AocError> {
+    input.races(Kerning::Joined)?.ways_to_win()
+}
+
+pub struct Day06;
+
+impl Solution for Day06 {
+    const DAY: u8 = 6;
+    const TITLE: &'static str = "Wait For It";
+
+    type Input = RaceSheet;
+    type Output = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, AocError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Output, AocError> {
+        solve1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Option<Result<Self::Output, AocError>> {
+        Some(solve2(input))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = include_str!("../inputs/2023/day06.sample.txt");
+
+    #[test]
+    fn test_num_record_breaks() {
+        let log = RaceLog::parse(SAMPLE, Kerning::Spaced).unwrap();
+        let breaks = log
+            .races
+            .iter()
+            .map(Race::num_record_breaks)
+            .collect::<Vec<_>>();
+
+        assert_eq!(breaks, vec![4, 8, 9]);
+    }
+
+    #[test]
+    fn test_parse_kerning() {
+        let races = |log: RaceLog| {
+            log.races
+                .iter()
+                .map(|r| (r.time, r.distance_record))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            races(RaceLog::parse(SAMPLE, Kerning::Spaced).unwrap()),
+            vec![(7, 9), (15, 40), (30, 200)]
+        );
+        assert_eq!(
+            races(RaceLog::parse(SAMPLE, Kerning::Joined).unwrap()),
+            vec![(71530, 940200)]
+        );
+
+        let messy = "\r\n  Time:  7  15   30  \r\n\r\n Distance:9 40 200\r\n\r\n";
+
+        assert_eq!(
+            races(RaceLog::parse(messy, Kerning::Spaced).unwrap()),
+            vec![(7, 9), (15, 40), (30, 200)]
+        );
+        assert_eq!(
+            races(RaceLog::parse(messy, Kerning::Joined).unwrap()),
+            vec![(71530, 940200)]
+        );
+    }
+
+    #[test]
+    fn test_race_sheet() {
+        let sheet = RaceSheet::parse(SAMPLE).unwrap();
+
+        assert_eq!(solve1(&sheet), Ok(288));
+        assert_eq!(solve2(&sheet), Ok(71503));
+
+        // only joining the digits overflows, which is a problem for part 2 alone
+        let input =
+            "Time: 8000000000 8000000000\nDistance: 15999999999999999999 15999999999999999999";
+        let sheet = RaceSheet::parse(input).unwrap();
+
+        assert_eq!(solve1(&sheet), Ok(1));
+        assert_eq!(
+            solve2(&sheet),
+            Err(AocError::parse(
+                1,
+                7,
+                "a number that fits in 64 bits",
+                "Time: 8000000000 8000000000"
+            ))
+        );
+
+        // errors found after parsing point at the same place RaceLog::parse does
+        for input in [
+            "Time: 7 15 30\r\n\r\n  Distance: 9 40",
+            "Time:\n7 99999999999999999999\nDistance: 9 1",
+        ] {
+            let sheet = RaceSheet::parse(input).unwrap();
+
+            for kerning in [Kerning::Spaced, Kerning::Joined] {
+                assert_eq!(
+                    sheet.races(kerning).err(),
+                    RaceLog::parse(input, kerning).err()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            RaceLog::parse("Time: 7 15 30\nDistance: 9 40", Kerning::Spaced).err(),
+            Some(AocError::parse(
+                2,
+                1,
+                "as many record distances as race times",
+                "Distance: 9 40"
+            ))
+        );
+        assert_eq!(
+            RaceLog::parse("Time: 7 15\nDistance: 9 4o", Kerning::Joined).err(),
+            Some(AocError::parse(
+                2,
+                14,
+                "a number or end of line",
+                "Distance: 9 4o"
+            ))
+        );
+        assert_eq!(
+            RaceLog::parse(
+                "Time: 99999999999 99999999999\nDistance: 1",
+                Kerning::Joined
+            )
+            .err(),
+            Some(AocError::parse(
+                1,
+                7,
+                "a number that fits in 64 bits",
+                "Time: 99999999999 99999999999"
+            ))
+        );
+
+        // with spaced kerning, the one group that doesn't fit
+        let input = "Time: 7 99999999999999999999
+Distance: 9 1";
+
+        assert_eq!(
+            RaceLog::parse(input, Kerning::Spaced).err(),
+            Some(AocError::parse(
+                1,
+                9,
+                "a number that fits in 64 bits",
+                "Time: 7 99999999999999999999"
+            ))
+        );
+        assert_eq!(
+            solve1(&RaceSheet::parse(input).unwrap()),
+            RaceLog::parse(input, Kerning::Spaced).map(|_| 0)
+        );
+    }
+
+    #[test]
+    fn test_closed_form_matches_brute_force() {
+        for time in 0..60 {
+            // up to past the best possible distance, (time / 2) * (time - time / 2)
+            for distance_record in 0..=time * time / 4 + 2 {
+                let race = Race {
+                    time,
+                    distance_record,
+                };
+
+                assert_eq!(
+                    race.num_record_breaks(),
+                    race.num_record_breaks_brute_force(),
+                    "time {} record {}",
+                    time,
+                    distance_record
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_analysis_matches_ground_truth() {
+        for time in 0..40 {
+            for distance_record in 0..=time * time / 4 + 2 {
+                let race = Race::new(time, distance_record);
+                let winning = (0..=time)
+                    .map(|hold| (hold, (hold * (time - hold)) as u128))
+                    .filter(|&(_, distance)| distance > distance_record as u128)
+                    .collect::<Vec<_>>();
+
+                assert_eq!(race.winning_strategies().collect::<Vec<_>>(), winning);
+                assert_eq!(
+                    race.winning_interval(),
+                    winning
+                        .first()
+                        .zip(winning.last())
+                        .map(|(lo, hi)| lo.0..=hi.0)
+                );
+
+                let furthest = (0..=time).map(|hold| race.distance(hold)).max();
+                assert_eq!(Some(race.distance(race.best_hold())), furthest);
+            }
+        }
+    }
+
+    #[test]
+    fn test_margin() {
+        let race = Race::new(7, 9);
+
+        assert_eq!(race.margin(0), -9);
+        assert_eq!(race.margin(2), 1);
+        assert_eq!(race.margin(3), 3);
+        assert_eq!(race.margin(7), -9);
+        assert_eq!(race.margin(100), -9);
+        assert_eq!(race.winning_interval(), Some(2..=5));
+        assert_eq!(race.best_hold(), 3);
+
+        let race = Race::new(u64::MAX, u64::MAX);
+        let best = race.best_hold();
+
+        assert_eq!(
+            race.margin(best),
+            (best as i128) * (best as i128 + 1) - u64::MAX as i128
+        );
+    }
 
-    log.races
-        .iter()
-        .fold(1, |wins, race| wins * race.num_record_breaks())
+    #[test]
+    fn test_tied_record() {
+        // holding for 5 of 10 goes exactly 25, which ties rather than beats the record
+        let race = |distance_record| Race {
+            time: 10,
+            distance_record,
+        };
+
+        assert_eq!(race(25).num_record_breaks(), 0);
+        assert_eq!(race(24).num_record_breaks(), 1);
+        // 3 and 7 tie at 21, leaving 4, 5 and 6
+        assert_eq!(race(21).num_record_breaks(), 3);
+    }
+
+    #[test]
+    fn test_large_races() {
+        let race = |time, distance_record| Race {
+            time,
+            distance_record,
+        };
+
+        // every hold but 0 and u64::MAX goes at least u64::MAX - 1
+        assert_eq!(race(u64::MAX, 0).num_record_breaks(), u64::MAX - 1);
+        assert_eq!(
+            race(u64::MAX, u64::MAX - 2).num_record_breaks(),
+            u64::MAX - 1
+        );
+        assert_eq!(
+            race(u64::MAX, u64::MAX - 1).num_record_breaks(),
+            u64::MAX - 3
+        );
+
+        // 2^32 * 2^32 is the best distance for time 2^33
+        assert_eq!(race(1 << 33, u64::MAX).num_record_breaks(), 1);
+        assert_eq!(race(1 << 32, (1 << 62) - 1).num_record_breaks(), 1);
+        assert_eq!(race(1 << 32, 1 << 62).num_record_breaks(), 0);
+
+        assert_eq!(race(71530, 940200).num_record_breaks(), 71503);
+    }
 }
@@ -1,320 +1,373 @@
-use std::cmp::{min, Ordering};
-use std::collections::hash_map::RandomState;
-use std::collections::HashMap;
-use std::iter::{IntoIterator, Iterator};
-
-use nom::bytes::complete::take;
-use nom::character::complete::{digit1, newline, space1};
-use nom::multi::separated_list1;
-use nom::sequence::separated_pair;
-use nom::{IResult, Parser};
-
-#[derive(Hash, Eq, Debug, PartialEq)]
-struct Cards(String);
+use std::fmt::{self, Display};
+use std::iter::Iterator;
+use std::str::FromStr;
 
-impl Cards {
-    fn value_ord(&self, other: &Self) -> Ordering {
-        let strength: HashMap<char, usize, RandomState> = HashMap::from_iter(
-            [
-                'A', 'K', 'Q', 'T', '9', '8', '7', '6', '5', '4', '3', '2', 'J',
-            ]
-            .into_iter()
-            .rev()
-            .enumerate()
-            .map(|(i, c)| (c, i + 1)),
-        );
+use nom::character::complete::space1;
+use nom::error::context;
+use nom::multi::count;
+use nom::sequence::separated_pair;
+use nom::Parser;
+
+use crate::cards::{Cards, Game, HandKind};
+use crate::error::AocError;
+use crate::parsing::{parse_all, parse_lines, unsigned, ParseResult};
+use crate::solution::Solution;
+
+/// The kinds of hand in Camel Cards, weakest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HandType {
+    /// All labels are distinct: 23456
+    HighCard,
+    /// Two cards share one label, the other three are all different: A23A4
+    OnePair,
+    /// Two cards share one label, two others share a second and one has a third: 23432
+    TwoPair,
+    /// Three cards share one label, the other two are different from everything: TTT98
+    ThreeOfAKind,
+    /// Three cards share one label and the other two share another: 23332
+    FullHouse,
+    /// Four cards share one label: AA8AA
+    FourOfAKind,
+    /// All five cards share one label: AAAAA
+    FiveOfAKind,
+}
 
-        self.0
-            .chars()
-            .zip(other.0.chars())
-            .find_map(|(a, b)| {
-                if a != b {
-                    Some(strength.get(&a).unwrap().cmp(strength.get(&b).unwrap()))
-                } else {
-                    None
-                }
-            })
-            .unwrap_or(Ordering::Equal)
+impl HandType {
+    /// Every hand type, weakest first.
+    pub const ALL: [HandType; 7] = [
+        HandType::HighCard,
+        HandType::OnePair,
+        HandType::TwoPair,
+        HandType::ThreeOfAKind,
+        HandType::FullHouse,
+        HandType::FourOfAKind,
+        HandType::FiveOfAKind,
+    ];
+
+    /// The type of `cards` under `game`, or None if `game` makes it a kind of hand that
+    /// Camel Cards doesn't have, like a straight.
+    pub fn of(cards: &Cards, game: &Game) -> Option<HandType> {
+        HandType::try_from(game.classify(cards).kind).ok()
     }
 }
 
-impl Ord for Cards {
-    fn cmp(&self, other: &Self) -> Ordering {
-        let by_type = self.r#type().cmp(&other.r#type());
+/// The engine's kind of hand, or back the kind it is if Camel Cards doesn't have it.
+impl TryFrom<HandKind> for HandType {
+    type Error = HandKind;
+
+    fn try_from(kind: HandKind) -> Result<Self, Self::Error> {
+        match kind {
+            HandKind::HighCard => Ok(HandType::HighCard),
+            HandKind::OnePair => Ok(HandType::OnePair),
+            HandKind::TwoPair => Ok(HandType::TwoPair),
+            HandKind::ThreeOfAKind => Ok(HandType::ThreeOfAKind),
+            HandKind::FullHouse => Ok(HandType::FullHouse),
+            HandKind::FourOfAKind => Ok(HandType::FourOfAKind),
+            HandKind::FiveOfAKind => Ok(HandType::FiveOfAKind),
+            HandKind::Straight | HandKind::Flush | HandKind::StraightFlush => Err(kind),
+        }
+    }
+}
 
-        if by_type == Ordering::Equal {
-            Cards::value_ord(self, other)
-        } else {
-            by_type
+impl From<HandType> for HandKind {
+    fn from(hand_type: HandType) -> Self {
+        match hand_type {
+            HandType::HighCard => HandKind::HighCard,
+            HandType::OnePair => HandKind::OnePair,
+            HandType::TwoPair => HandKind::TwoPair,
+            HandType::ThreeOfAKind => HandKind::ThreeOfAKind,
+            HandType::FullHouse => HandKind::FullHouse,
+            HandType::FourOfAKind => HandKind::FourOfAKind,
+            HandType::FiveOfAKind => HandKind::FiveOfAKind,
         }
     }
 }
 
-impl PartialOrd for Cards {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+impl Display for HandType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        HandKind::from(*self).fmt(f)
     }
 }
 
+/// Every hand with its rank, weakest first from rank 1. Identical hands are still ranked one
+/// after the other, in the order they appear in the input, so the earlier one gets the lower rank.
 #[derive(Debug)]
-struct Ranking(Vec<(u32, Hand)>);
-
-impl Ranking {
-    fn from(hands: &[Hand]) -> Ranking {
-        let bids = Cards::bids_by_hand(hands);
-        let mut cards: Vec<Cards> = Vec::new();
-
-        hands.iter().for_each(|h| cards.push(h.cards.clone()));
-
-        cards.sort();
-
-        Ranking(
-            cards
-                .into_iter()
-                .enumerate()
-                .map(|(i, cards)| {
-                    (
-                        (i + 1) as u32,
-                        Hand {
-                            cards: cards.clone(),
-                            bid: *bids.get(&cards).unwrap(),
-                        },
-                    )
-                })
-                .collect(),
-        )
+struct Ranking<'a>(Vec<(u32, &'a Hand)>);
+
+impl<'a> Ranking<'a> {
+    fn from(hands: &'a [Hand], game: &Game) -> Result<Ranking<'a>, AocError> {
+        game.rank(hands, |h| &h.cards).map(Ranking)
     }
 
-    fn winnings(&self) -> u32 {
+    fn winnings(&self) -> Result<u32, AocError> {
         self.0
             .iter()
-            .fold(0, |sum, (rank, hand)| sum + (rank * hand.bid))
-    }
-}
-
-impl Clone for Cards {
-    fn clone(&self) -> Self {
-        Cards(self.0.clone())
+            .try_fold(0u32, |sum, (rank, hand)| {
+                rank.checked_mul(hand.bid)
+                    .and_then(|winnings| sum.checked_add(winnings))
+            })
+            .ok_or(AocError::Overflow("total winnings"))
     }
 }
 
-#[derive(Debug, Eq, PartialOrd, PartialEq)]
+#[derive(Debug, Eq, PartialEq)]
 pub struct Hand {
     cards: Cards,
     bid: u32,
 }
 
 impl Hand {
+    #[cfg(test)]
     fn new(cards: &str, bid: u32) -> Hand {
         Hand {
-            cards: Cards::new(cards),
+            cards: tests::cards(cards),
             bid,
         }
     }
 }
 
-impl Ord for Hand {
-    fn cmp(&self, other: &Self) -> Ordering {
-        Cards::cmp(&self.cards, &other.cards)
-    }
-}
-
-fn parse_hand(input: &str) -> IResult<&str, Hand> {
-    separated_pair(take(5usize), space1, digit1)
+// e.g. "32T3K 765", where both presets take the same labels
+fn parse_hand(input: &str) -> ParseResult<'_, Hand> {
+    let game = &Game::CAMEL_CARDS;
+    let cards = count(
+        context("a card (one of AKQJT98765432)", game.card()),
+        game.hand_size,
+    )
+    .map(Cards);
+    let bid = context("bid", unsigned);
+
+    separated_pair(cards, space1, bid)
         .parse(input)
-        .map(|(s, (cards, bid))| {
-            (
-                s,
-                Hand {
-                    cards: Cards(cards.to_string()),
-                    bid: bid.parse().unwrap(),
-                },
-            )
-        })
+        .map(|(s, (cards, bid))| (s, Hand { cards, bid }))
 }
 
-impl Cards {
-    fn new(s: &str) -> Self {
-        Cards(s.to_string())
-    }
+impl FromStr for Hand {
+    type Err = AocError;
 
-    fn bids_by_hand(hands: &[Hand]) -> HashMap<Cards, u32> {
-        let mut m = HashMap::new();
-        for hand in hands {
-            m.insert(hand.cards.clone(), hand.bid);
-        }
-        m
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_all(parse_hand, s)
     }
+}
 
-    fn r#type(&self) -> u8 {
-        let mut counts = HashMap::new();
-
-        self.0
-            .chars()
-            .for_each(|c| *counts.entry(c).or_insert(0) += 1);
-
-        let (&max_dupe_card, max_dupes) = counts
-            .iter()
-            .filter(|&(&c, _)| c != 'J')
-            .max_by_key(|&(_, count)| count)
-            .unwrap_or((&'X', &0));
-
-        let &jokers = counts.get(&'J').unwrap_or(&0);
-
-        match min(max_dupes + jokers, 5) {
-            //Five of a kind, where all five cards have the same label: AAAAA
-            5 => 7,
+#[aoc_generator(day7)]
+pub fn input_generator(input: &str) -> Result<Vec<Hand>, AocError> {
+    parse_lines(input)
+}
 
-            // Four of a kind, where four cards have the same label and one card has a different label: AA8AA
-            4 => 6,
+#[aoc(day7, part1)]
+pub fn solve1(input: &[Hand]) -> Result<u32, AocError> {
+    Ranking::from(input, &Game::CAMEL_CARDS)?.winnings()
+}
 
-            // Full house, where three cards have the same label, and the remaining two cards share a different label: 23332
-            3 if counts
-                .iter()
-                .any(|(&card, &count)| card != max_dupe_card && card != 'J' && count == 2) =>
-            {
-                5
-            }
+#[aoc(day7, part2)]
+pub fn solve2(input: &[Hand]) -> Result<u32, AocError> {
+    Ranking::from(input, &Game::CAMEL_CARDS_JOKERS)?.winnings()
+}
 
-            // Three of a kind, where three cards have the same label, and the remaining two cards are each different from any other card in the hand: TTT98
-            3 => 4,
+pub struct Day07;
 
-            // Two pair, where two cards share one label, two other cards share a second label, and the remaining card has a third label: 23432
-            2 if counts
-                .iter()
-                .filter(|&(_, &c)| c == 2)
-                .collect::<Vec<_>>()
-                .len()
-                == 2 =>
-            {
-                3
-            }
+impl Solution for Day07 {
+    const DAY: u8 = 7;
+    const TITLE: &'static str = "Camel Cards";
 
-            // One pair, where two cards share one label, and the other three cards have a different label from the pair and each other: A23A4
-            2 => 2,
+    type Input = Vec<Hand>;
+    type Output = u32;
 
-            // High card, where all cards' labels are distinct: 23456
-            _ => 1,
-        }
+    fn parse(input: &str) -> Result<Self::Input, AocError> {
+        input_generator(input)
     }
-}
 
-fn parse_input(input: &str) -> IResult<&str, Vec<Hand>> {
-    let (s, hands) = separated_list1(newline, parse_hand)(input)?;
-    Ok((s, hands))
-}
-
-#[aoc(day7, part1)]
-pub fn solve1(input: &str) -> u32 {
-    let (_, h) = parse_input(input).unwrap();
-    let ranking = Ranking::from(&h);
+    fn part1(input: &Self::Input) -> Result<Self::Output, AocError> {
+        solve1(input)
+    }
 
-    ranking.winnings()
+    fn part2(input: &Self::Input) -> Option<Result<Self::Output, AocError>> {
+        Some(solve2(input))
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::cmp::Ordering;
+
     use super::*;
 
+    pub(super) fn cards(s: &str) -> Cards {
+        Game::CAMEL_CARDS.parse_cards(s).unwrap()
+    }
+
+    // weakest first, the way `Ranking` orders them
+    fn ranked(hands: Vec<Cards>, game: &Game) -> Vec<Cards> {
+        game.rank(&hands, |c| c)
+            .unwrap()
+            .into_iter()
+            .map(|(_, c)| c.clone())
+            .collect()
+    }
+
+    // the plain comparison, by hand type first and then card by card from the left, kept as
+    // an oracle for what `Game::key` encodes
+    fn cmp_with(a: &Cards, b: &Cards, game: &Game) -> Ordering {
+        let strength = |rank: char| game.ranks.len() - game.ranks.find(rank).unwrap();
+
+        HandType::of(a, game)
+            .cmp(&HandType::of(b, game))
+            .then_with(|| {
+                a.0.iter()
+                    .zip(&b.0)
+                    .find(|(x, y)| x != y)
+                    .map_or(Ordering::Equal, |(x, y)| {
+                        strength(x.rank).cmp(&strength(y.rank))
+                    })
+            })
+    }
+
     #[test]
     fn test_hand_type() {
         // 1. Five of a kind, where all five cards have the same label: AAAAA
-        assert_eq!(Cards::new("AAAAA").r#type(), 7);
-        assert_eq!(Cards::new("JJJJJ").r#type(), 7);
+        assert_eq!(
+            HandType::of(&cards("AAAAA"), &Game::CAMEL_CARDS_JOKERS),
+            Some(HandType::FiveOfAKind)
+        );
+        assert_eq!(
+            HandType::of(&cards("JJJJJ"), &Game::CAMEL_CARDS_JOKERS),
+            Some(HandType::FiveOfAKind)
+        );
 
         // 2. Four of a kind, where four cards have the same label and one card has a different label: AA8AA
-        assert_eq!(Cards::new("AA8AA").r#type(), 6);
+        assert_eq!(
+            HandType::of(&cards("AA8AA"), &Game::CAMEL_CARDS_JOKERS),
+            Some(HandType::FourOfAKind)
+        );
 
         // 3. Full house, where three cards have the same label, and the remaining two cards share a different label: 23332
-        assert_eq!(Cards::new("23332").r#type(), 5);
+        assert_eq!(
+            HandType::of(&cards("23332"), &Game::CAMEL_CARDS_JOKERS),
+            Some(HandType::FullHouse)
+        );
 
         // 4. Three of a kind, where three cards have the same label, and the remaining two cards are each different from any other card in the hand: TTT98
-        assert_eq!(Cards::new("TTT98").r#type(), 4);
+        assert_eq!(
+            HandType::of(&cards("TTT98"), &Game::CAMEL_CARDS_JOKERS),
+            Some(HandType::ThreeOfAKind)
+        );
 
         // Two pair, where two cards share one label, two other cards share a second label, and the remaining card has a third label: 23432
-        assert_eq!(Cards::new("23432").r#type(), 3);
+        assert_eq!(
+            HandType::of(&cards("23432"), &Game::CAMEL_CARDS_JOKERS),
+            Some(HandType::TwoPair)
+        );
 
         // One pair, where two cards share one label, and the other three cards have a different label from the pair and each other: A23A4
-        assert_eq!(Cards::new("A23A4").r#type(), 2);
+        assert_eq!(
+            HandType::of(&cards("A23A4"), &Game::CAMEL_CARDS_JOKERS),
+            Some(HandType::OnePair)
+        );
 
         // High card, where all cards' labels are distinct: 23456
-        assert_eq!(Cards::new("23456").r#type(), 1);
+        assert_eq!(
+            HandType::of(&cards("23456"), &Game::CAMEL_CARDS_JOKERS),
+            Some(HandType::HighCard)
+        );
     }
 
     #[test]
     fn test_sort() {
-        let mut cc = vec![
-            Cards::new("32T3K"),
-            Cards::new("T55J5"),
-            Cards::new("KK677"),
-            Cards::new("KTJJT"),
-            Cards::new("QQQJA"),
+        let cc = vec![
+            cards("32T3K"),
+            cards("T55J5"),
+            cards("KK677"),
+            cards("KTJJT"),
+            cards("QQQJA"),
         ];
 
-        cc.sort();
+        let cc = ranked(cc, &Game::CAMEL_CARDS_JOKERS);
 
         assert_eq!(
             cc,
             vec![
-                Cards::new("32T3K"),
-                Cards::new("KK677"),
-                Cards::new("T55J5"),
-                Cards::new("QQQJA"),
-                Cards::new("KTJJT"),
+                cards("32T3K"),
+                cards("KK677"),
+                cards("T55J5"),
+                cards("QQQJA"),
+                cards("KTJJT"),
             ]
         )
     }
 
     #[test]
     fn test_sort_joker_full() {
-        let mut cc = vec![
-            Cards::new("AJJJJ"),
-            Cards::new("JJJJJ"),
-            Cards::new("AAJJJ"),
-            Cards::new("AAAAJ"),
-            Cards::new("AAAJJ"),
-            Cards::new("AAAAA"),
+        let cc = vec![
+            cards("AJJJJ"),
+            cards("JJJJJ"),
+            cards("AAJJJ"),
+            cards("AAAAJ"),
+            cards("AAAJJ"),
+            cards("AAAAA"),
         ];
 
-        cc.sort();
+        let cc = ranked(cc, &Game::CAMEL_CARDS_JOKERS);
 
         assert_eq!(
             cc,
             vec![
-                Cards::new("JJJJJ"),
-                Cards::new("AJJJJ"),
-                Cards::new("AAJJJ"),
-                Cards::new("AAAJJ"),
-                Cards::new("AAAAJ"),
-                Cards::new("AAAAA"),
+                cards("JJJJJ"),
+                cards("AJJJJ"),
+                cards("AAJJJ"),
+                cards("AAAJJ"),
+                cards("AAAAJ"),
+                cards("AAAAA"),
             ]
         )
     }
 
     #[test]
     fn test_sort_joker_3() {
-        let mut cc = vec![
-            Cards::new("222JJ"), // 5  of a kind
-            Cards::new("22233"), // full house
-            Cards::new("222J3"), // 4 of a kind
+        let cc = vec![
+            cards("222JJ"), // 5  of a kind
+            cards("22233"), // full house
+            cards("222J3"), // 4 of a kind
         ];
 
-        cc.sort();
+        let cc = ranked(cc, &Game::CAMEL_CARDS_JOKERS);
+
+        assert_eq!(cc, vec![cards("22233"), cards("222J3"), cards("222JJ")])
+    }
 
+    #[test]
+    fn test_hand_type_other_games() {
+        let poker = |s: &str| Game::POKER.parse_cards(s).unwrap();
+
+        assert_eq!(HandType::of(&poker("AhKhQhJhTh"), &Game::POKER), None);
+        assert_eq!(HandType::of(&poker("2c3d4h5s6c"), &Game::POKER), None);
         assert_eq!(
-            cc,
-            vec![
-                Cards::new("22233"),
-                Cards::new("222J3"),
-                Cards::new("222JJ")
-            ]
-        )
+            HandType::of(&poker("9c9d9h2s2c"), &Game::POKER),
+            Some(HandType::FullHouse)
+        );
+
+        assert_eq!(
+            HandType::try_from(HandKind::StraightFlush),
+            Err(HandKind::StraightFlush)
+        );
+        for hand_type in HandType::ALL {
+            assert_eq!(HandType::try_from(HandKind::from(hand_type)), Ok(hand_type));
+        }
     }
+
     #[test]
     fn test_full_house() {
-        assert_eq!(Cards::new("2233J").r#type(), 5);
-        assert_eq!(Cards::new("JJJ34").r#type(), 6);
-        assert_eq!(Cards::new("J2345").r#type(), 2);
+        assert_eq!(
+            HandType::of(&cards("2233J"), &Game::CAMEL_CARDS_JOKERS),
+            Some(HandType::FullHouse)
+        );
+        assert_eq!(
+            HandType::of(&cards("JJJ34"), &Game::CAMEL_CARDS_JOKERS),
+            Some(HandType::FourOfAKind)
+        );
+        assert_eq!(
+            HandType::of(&cards("J2345"), &Game::CAMEL_CARDS_JOKERS),
+            Some(HandType::OnePair)
+        );
     }
 
     #[test]
@@ -341,36 +394,259 @@ mod tests {
             Hand::new("JJJJ2", 41),
         ];
 
-        let r = Ranking::from(&hands);
+        let r = Ranking::from(&hands, &Game::CAMEL_CARDS_JOKERS).unwrap();
 
-        assert_eq!(r.winnings(), 6839)
+        assert_eq!(r.winnings(), Ok(6839))
     }
 
     #[test]
     fn test_winnings() {
         let cc = vec![
             Hand {
-                cards: Cards::new("32T3K"),
+                cards: cards("32T3K"),
                 bid: 765,
             },
             Hand {
-                cards: Cards::new("T55J5"),
+                cards: cards("T55J5"),
                 bid: 684,
             },
             Hand {
-                cards: Cards::new("KK677"),
+                cards: cards("KK677"),
                 bid: 28,
             },
             Hand {
-                cards: Cards::new("KTJJT"),
+                cards: cards("KTJJT"),
                 bid: 220,
             },
             Hand {
-                cards: Cards::new("QQQJA"),
+                cards: cards("QQQJA"),
                 bid: 483,
             },
         ];
 
-        assert_eq!(Ranking::from(&cc).winnings(), 5905)
+        assert_eq!(
+            Ranking::from(&cc, &Game::CAMEL_CARDS).unwrap().winnings(),
+            Ok(6440)
+        );
+        assert_eq!(
+            Ranking::from(&cc, &Game::CAMEL_CARDS_JOKERS)
+                .unwrap()
+                .winnings(),
+            Ok(5905)
+        );
+    }
+
+    #[test]
+    fn test_duplicate_hands() {
+        let hands = vec![
+            Hand::new("KK677", 28),
+            Hand::new("32T3K", 765),
+            Hand::new("KK677", 100),
+            Hand::new("32T3K", 2),
+        ];
+
+        let r = Ranking::from(&hands, &Game::CAMEL_CARDS).unwrap();
+
+        // every bid survives, and identical hands are ranked in input order
+        assert_eq!(
+            r.0.iter()
+                .map(|&(rank, hand)| (rank, hand.cards.to_string(), hand.bid))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, "32T3K".to_string(), 765),
+                (2, "32T3K".to_string(), 2),
+                (3, "KK677".to_string(), 28),
+                (4, "KK677".to_string(), 100),
+            ]
+        );
+        assert_eq!(r.winnings(), Ok(765 + 2 * 2 + 3 * 28 + 4 * 100));
+    }
+
+    #[test]
+    fn test_duplicate_sample() {
+        let input = "32T3K 765\nT55J5 684\nKK677 28\nKTJJT 220\nQQQJA 483";
+        let once = input_generator(input).unwrap();
+        let twice = input_generator(&format!("{}\n{}", input, input)).unwrap();
+
+        // doubling every hand gives each pair of copies ranks 2r-1 and 2r
+        let expected = |hands: &[Hand], game: &Game| {
+            Ranking::from(hands, game)
+                .unwrap()
+                .0
+                .iter()
+                .map(|&(rank, hand)| (4 * rank - 1) * hand.bid)
+                .sum::<u32>()
+        };
+
+        assert_eq!(solve1(&twice), Ok(expected(&once, &Game::CAMEL_CARDS)));
+        assert_eq!(
+            solve2(&twice),
+            Ok(expected(&once, &Game::CAMEL_CARDS_JOKERS))
+        );
+    }
+
+    #[test]
+    fn test_jacks() {
+        let game = Game::CAMEL_CARDS;
+
+        assert_eq!(
+            HandType::of(&cards("JJJJJ"), &game),
+            Some(HandType::FiveOfAKind)
+        );
+        assert_eq!(
+            HandType::of(&cards("JJJ34"), &game),
+            Some(HandType::ThreeOfAKind)
+        );
+        assert_eq!(
+            HandType::of(&cards("2233J"), &game),
+            Some(HandType::TwoPair)
+        );
+        assert_eq!(
+            HandType::of(&cards("J2345"), &game),
+            Some(HandType::HighCard)
+        );
+
+        let cc = vec![
+            cards("T55J5"),
+            cards("QQQJA"),
+            cards("JKKK2"),
+            cards("QQQQ2"),
+            cards("T55Q5"),
+        ];
+
+        let cc = ranked(cc, &game);
+
+        // J beats T but loses to Q when it's a plain jack
+        assert_eq!(
+            cc,
+            vec![
+                cards("T55J5"),
+                cards("T55Q5"),
+                cards("JKKK2"),
+                cards("QQQJA"),
+                cards("QQQQ2"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_classify() {
+        let explain = |s: &str, game: &Game| game.classify(&cards(s)).to_string();
+
+        assert_eq!(
+            explain("AAJJ2", &Game::CAMEL_CARDS_JOKERS),
+            "JJ → A makes four of a kind"
+        );
+        assert_eq!(explain("AAJJ2", &Game::CAMEL_CARDS), "two pair");
+        assert_eq!(
+            explain("JJJJJ", &Game::CAMEL_CARDS_JOKERS),
+            "JJJJJ → A makes five of a kind"
+        );
+        assert_eq!(
+            explain("2233J", &Game::CAMEL_CARDS_JOKERS),
+            "J → 3 makes full house"
+        );
+        assert_eq!(
+            explain("J2345", &Game::CAMEL_CARDS_JOKERS),
+            "J → 5 makes one pair"
+        );
+        assert_eq!(
+            explain("KTJJT", &Game::CAMEL_CARDS_JOKERS),
+            "JJ → T makes four of a kind"
+        );
+
+        assert!(HandType::HighCard < HandType::OnePair);
+        assert!(HandType::FullHouse < HandType::FourOfAKind);
+        assert_eq!(HandType::TwoPair.to_string(), "two pair");
+    }
+
+    // deterministic pseudo-random hands, a simple LCG is plenty for shuffling labels
+    fn random_hands(n: usize) -> Vec<Hand> {
+        let mut state = 0x2023_0007u64;
+        let mut next = move || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 33) as usize
+        };
+        let labels = Game::CAMEL_CARDS.ranks.as_bytes();
+
+        (0..n)
+            .map(|_| Hand {
+                cards: cards(
+                    &(0..5)
+                        .map(|_| labels[next() % 13] as char)
+                        .collect::<String>(),
+                ),
+                bid: (next() % 1000) as u32 + 1,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_key_matches_comparison() {
+        let hands = random_hands(2000);
+
+        for game in [Game::CAMEL_CARDS, Game::CAMEL_CARDS_JOKERS] {
+            for pair in hands.windows(2) {
+                let (a, b) = (&pair[0].cards, &pair[1].cards);
+
+                assert_eq!(
+                    game.key(a).cmp(&game.key(b)),
+                    cmp_with(a, b, &game),
+                    "{} vs {} with {}",
+                    a,
+                    b,
+                    game.name
+                );
+            }
+        }
+
+        assert_eq!(Game::CAMEL_CARDS.key(&cards("32T3K")), 0x1_2_1_9_2_C);
+        assert_eq!(Game::CAMEL_CARDS_JOKERS.key(&cards("JJJJJ")), 0x6_1_1_1_1_1);
+    }
+
+    // cargo test --release -- --ignored --nocapture bench_key
+    #[test]
+    #[ignore]
+    fn bench_key_against_comparison() {
+        use std::time::Instant;
+
+        let hands = random_hands(200_000);
+        let all = hands.iter().map(|h| h.cards.clone()).collect::<Vec<_>>();
+
+        for game in [Game::CAMEL_CARDS, Game::CAMEL_CARDS_JOKERS] {
+            let mut compared = all.clone();
+            let start = Instant::now();
+            compared.sort_by(|a, b| cmp_with(a, b, &game));
+            let comparison = start.elapsed();
+
+            let mut keyed = all.clone();
+            let start = Instant::now();
+            keyed.sort_by_cached_key(|c| game.key(c));
+            let key = start.elapsed();
+
+            assert_eq!(compared, keyed);
+            eprintln!(
+                "{} hands, {}: comparison {:?}, key {:?}",
+                all.len(),
+                game.name,
+                comparison,
+                key
+            );
+        }
+    }
+
+    #[test]
+    fn test_unknown_card() {
+        assert_eq!(
+            input_generator("32T3K 765\nT55X5 684"),
+            Err(AocError::parse(
+                2,
+                4,
+                "a card (one of AKQJT98765432)",
+                "T55X5 684"
+            ))
+        )
     }
 }
@@ -0,0 +1,259 @@
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::path::PathBuf;
+
+use nom::error::{VerboseError, VerboseErrorKind};
+
+/// Everything that can go wrong while parsing or solving a puzzle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AocError {
+    /// The input didn't match what the parser expected. `line` and `column` are 1-based, and
+    /// `source_line` is the text of the offending line (empty if it isn't known).
+    Parse {
+        line: usize,
+        column: usize,
+        expected: String,
+        source_line: String,
+    },
+    /// There was no input at all, usually an empty input file.
+    EmptyInput,
+    /// The input file doesn't exist.
+    MissingInput(PathBuf),
+    /// The input file exists but couldn't be read.
+    Io(PathBuf, String),
+    /// An intermediate value or the answer itself doesn't fit in the solver's integer type.
+    Overflow(&'static str),
+    /// A query named a category that nothing in the input mentions.
+    MissingCategory(String),
+    /// Both categories exist, but no chain of maps in the input leads from one to the other.
+    BrokenChain { from: String, to: String },
+    /// A card game's configuration can't be played, e.g. it has more ranks than fit a sort key.
+    InvalidGame(String),
+}
+
+impl AocError {
+    pub fn parse(
+        line: usize,
+        column: usize,
+        expected: impl Into<String>,
+        source_line: impl Into<String>,
+    ) -> AocError {
+        AocError::Parse {
+            line,
+            column,
+            expected: expected.into(),
+            source_line: source_line.into(),
+        }
+    }
+
+    /// Builds a parse error for a nom failure, where `source` is the complete text handed to the
+    /// parser. The position is taken from the innermost error and the expected construct from
+    /// the outermost `context` at that position, or the innermost `context` if none is there.
+    pub fn from_nom(source: &str, error: VerboseError<&str>) -> AocError {
+        let remaining = error.errors.first().map_or("", |&(remaining, _)| remaining);
+
+        let contexts = error.errors.iter().filter_map(|(rest, kind)| match kind {
+            VerboseErrorKind::Context(context) => Some((rest.len(), context)),
+            _ => None,
+        });
+
+        // several contexts can sit at the failure position, e.g. "game id" around the "a number"
+        // of a shared number parser, and the outermost one says the most about the input
+        let expected = contexts
+            .clone()
+            .rfind(|&(len, _)| len == remaining.len())
+            .or_else(|| contexts.clone().next())
+            .map(|(_, context)| context.to_string())
+            .or_else(|| {
+                error.errors.first().map(|(_, kind)| match kind {
+                    VerboseErrorKind::Char(c) => format!("'{}'", c),
+                    VerboseErrorKind::Nom(kind) => kind.description().to_lowercase(),
+                    VerboseErrorKind::Context(context) => context.to_string(),
+                })
+            })
+            .unwrap_or_else(|| "valid input".to_string());
+
+        let offset = source.len() - remaining.len();
+        let (line, column) = line_and_column(source, offset);
+
+        AocError::parse(line, column, expected, line_at(source, offset))
+    }
+
+    /// Moves a parse error in a part of the input that starts `lines` lines further down.
+    pub fn shift_lines(self, lines: usize) -> AocError {
+        match self {
+            AocError::Parse { line, .. } => {
+                let shifted = line + lines;
+                self.on_line(shifted)
+            }
+            e => e,
+        }
+    }
+
+    /// Moves a parse error for a single line of input to its real line number.
+    pub fn on_line(self, line: usize) -> AocError {
+        match self {
+            AocError::Parse {
+                column,
+                expected,
+                source_line,
+                ..
+            } => AocError::Parse {
+                line,
+                column,
+                expected,
+                source_line,
+            },
+            e => e,
+        }
+    }
+}
+
+impl Display for AocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AocError::Parse {
+                line,
+                column,
+                expected,
+                source_line,
+            } => {
+                write!(
+                    f,
+                    "parse error at line {}, column {}: expected {}",
+                    line, column, expected
+                )?;
+
+                if source_line.is_empty() {
+                    return Ok(());
+                }
+
+                // rustc-style snippet with a caret under the offending column:
+                //
+                //  2 | Distance: x
+                //    |           ^
+                let gutter = line.to_string();
+                let padding = " ".repeat(gutter.len());
+
+                write!(
+                    f,
+                    "\n {} | {}\n {} | {}^",
+                    gutter,
+                    source_line,
+                    padding,
+                    " ".repeat(column.saturating_sub(1))
+                )
+            }
+            AocError::EmptyInput => write!(f, "input is empty"),
+            AocError::MissingInput(path) => write!(f, "input file {} not found", path.display()),
+            AocError::Io(path, e) => write!(f, "failed to read {}: {}", path.display(), e),
+            AocError::Overflow(what) => write!(f, "arithmetic overflow computing {}", what),
+            AocError::MissingCategory(name) => write!(f, "no map mentions category '{}'", name),
+            AocError::BrokenChain { from, to } => {
+                write!(f, "no chain of maps leads from '{}' to '{}'", from, to)
+            }
+            AocError::InvalidGame(why) => write!(f, "invalid card game: {}", why),
+        }
+    }
+}
+
+impl Error for AocError {}
+
+/// Rejects input that is empty or only whitespace.
+pub fn non_empty(input: &str) -> Result<&str, AocError> {
+    if input.trim().is_empty() {
+        Err(AocError::EmptyInput)
+    } else {
+        Ok(input)
+    }
+}
+
+/// 1-based line and column of the byte at `offset`, counting columns in chars.
+pub fn line_and_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+    (line, before[line_start..].chars().count() + 1)
+}
+
+/// The full line containing the byte at `offset`, without its line ending.
+pub fn line_at(source: &str, offset: usize) -> &str {
+    let start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let end = source[offset..]
+        .find('\n')
+        .map_or(source.len(), |i| offset + i);
+
+    source[start..end].trim_end_matches('\r')
+}
+
+#[cfg(test)]
+mod tests {
+    use nom::bytes::complete::tag;
+    use nom::character::complete::{digit1, line_ending, space1};
+    use nom::error::context;
+    use nom::sequence::tuple;
+    use nom::Finish;
+
+    use super::*;
+    use crate::parsing::ParseResult;
+
+    fn parse_times(s: &str) -> ParseResult<'_, (&str, &str, &str, &str)> {
+        tuple((
+            context("\"Time:\"", tag("Time:")),
+            space1,
+            context("race time", digit1),
+            line_ending,
+        ))(s)
+    }
+
+    #[test]
+    fn test_from_nom() {
+        let source = "Time: 7\nDistance: x";
+        let (rest, _) = parse_times(source).unwrap();
+        let error = context("record distance", tag("Distance: 9"))(rest)
+            .finish()
+            .unwrap_err();
+
+        assert_eq!(
+            AocError::from_nom(source, error),
+            AocError::parse(2, 1, "record distance", "Distance: x")
+        );
+
+        let error = parse_times("Time: seven").finish().unwrap_err();
+
+        assert_eq!(
+            AocError::from_nom("Time: seven", error),
+            AocError::parse(1, 7, "race time", "Time: seven")
+        );
+    }
+
+    #[test]
+    fn test_display_caret() {
+        let error = parse_times("Time: seven").finish().unwrap_err();
+
+        assert_eq!(
+            AocError::from_nom("Time: seven", error)
+                .on_line(12)
+                .to_string(),
+            [
+                "parse error at line 12, column 7: expected race time",
+                " 12 | Time: seven",
+                "    |       ^",
+            ]
+            .join("\n")
+        );
+
+        assert_eq!(
+            AocError::parse(1, 16, "a seed range length", "").to_string(),
+            "parse error at line 1, column 16: expected a seed range length"
+        );
+    }
+
+    #[test]
+    fn test_non_empty() {
+        assert_eq!(non_empty(""), Err(AocError::EmptyInput));
+        assert_eq!(non_empty(" \n"), Err(AocError::EmptyInput));
+        assert_eq!(non_empty("1abc2"), Ok("1abc2"));
+    }
+}
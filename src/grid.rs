@@ -0,0 +1,231 @@
+use std::fmt::{self, Display};
+use std::ops::{Index, IndexMut};
+
+use crate::error::{non_empty, AocError};
+
+/// A cell position in a [`Grid`], counted from the top left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Pos {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl Pos {
+    pub fn new(row: usize, col: usize) -> Pos {
+        Pos { row, col }
+    }
+
+    // None if the offset would take us above or left of the grid
+    fn offset(self, d_row: isize, d_col: isize) -> Option<Pos> {
+        Some(Pos {
+            row: self.row.checked_add_signed(d_row)?,
+            col: self.col.checked_add_signed(d_col)?,
+        })
+    }
+}
+
+impl Display for Pos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.row, self.col)
+    }
+}
+
+const ORTHOGONAL: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+
+const SURROUNDING: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// A rectangular grid of cells stored row by row, as found in puzzle inputs like day 3's engine
+/// schematic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl Grid<char> {
+    /// Parses one row per line, taking the width from the first line. Every other line has to
+    /// be the same width.
+    pub fn parse(input: &str) -> Result<Grid<char>, AocError> {
+        Grid::parse_with(input, |c| c)
+    }
+}
+
+impl<T> Grid<T> {
+    /// Like [`Grid::parse`], converting each character to a cell with `cell`.
+    pub fn parse_with<F>(input: &str, cell: F) -> Result<Grid<T>, AocError>
+    where
+        F: Fn(char) -> T,
+    {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = Vec::new();
+
+        for (i, line) in non_empty(input)?.lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+            let len = line.chars().count();
+            let &mut width = width.get_or_insert(len);
+
+            if len != width {
+                return Err(AocError::parse(
+                    i + 1,
+                    len.min(width) + 1,
+                    format!("a row of {} cells", width),
+                    line,
+                ));
+            }
+
+            cells.extend(line.chars().map(&cell));
+            height += 1;
+        }
+
+        Ok(Grid {
+            width: width.unwrap_or(0),
+            height,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, pos: Pos) -> bool {
+        pos.row < self.height && pos.col < self.width
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        if self.contains(pos) {
+            self.cells.get(pos.row * self.width + pos.col)
+        } else {
+            None
+        }
+    }
+
+    /// Every position in the grid, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |col| Pos::new(row, col)))
+    }
+
+    /// A grid of the same shape with every cell converted by `f`.
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // chunks panics on 0, which only an empty grid would have
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// The up to 4 positions above, below, left and right of `pos` that are inside the grid.
+    pub fn neighbours4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        self.neighbours(pos, &ORTHOGONAL)
+    }
+
+    /// The up to 8 positions surrounding `pos`, diagonals included, that are inside the grid.
+    pub fn neighbours8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        self.neighbours(pos, &SURROUNDING)
+    }
+
+    fn neighbours<'a>(
+        &'a self,
+        pos: Pos,
+        offsets: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = Pos> + 'a {
+        offsets
+            .iter()
+            .filter_map(move |&(d_row, d_col)| pos.offset(d_row, d_col))
+            .filter(|&p| self.contains(p))
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &Self::Output {
+        assert!(self.contains(pos), "{} is outside the grid", pos);
+        &self.cells[pos.row * self.width + pos.col]
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut Self::Output {
+        assert!(self.contains(pos), "{} is outside the grid", pos);
+        &mut self.cells[pos.row * self.width + pos.col]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let grid = Grid::parse("467.\n...*\r\n..35\n").unwrap();
+
+        assert_eq!((grid.width(), grid.height()), (4, 3));
+        assert_eq!(grid[Pos::new(1, 3)], '*');
+        assert_eq!(grid.get(Pos::new(2, 4)), None);
+        assert_eq!(grid.rows().nth(2), Some(&['.', '.', '3', '5'][..]));
+
+        let mut digits = grid.map(|c| c.to_digit(10));
+        digits[Pos::new(0, 3)] = Some(0);
+        assert_eq!(
+            digits.rows().next(),
+            Some(&[Some(4), Some(6), Some(7), Some(0)][..])
+        );
+
+        assert_eq!(
+            Grid::parse("467.\n..\n..35"),
+            Err(AocError::parse(2, 3, "a row of 4 cells", ".."))
+        );
+        assert_eq!(Grid::parse(""), Err(AocError::EmptyInput));
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = Grid::parse_with("123\n456\n789", |c| c.to_digit(10).unwrap()).unwrap();
+        let values = |ps: Vec<Pos>| ps.into_iter().map(|p| grid[p]).collect::<Vec<_>>();
+
+        assert_eq!(
+            values(grid.neighbours4(Pos::new(1, 1)).collect()),
+            vec![2, 4, 6, 8]
+        );
+        assert_eq!(
+            values(grid.neighbours4(Pos::new(0, 0)).collect()),
+            vec![2, 4]
+        );
+        assert_eq!(
+            values(grid.neighbours8(Pos::new(1, 1)).collect()),
+            vec![1, 2, 3, 4, 6, 7, 8, 9]
+        );
+
+        // no wrapping from the end of one row to the start of the next
+        assert_eq!(
+            values(grid.neighbours8(Pos::new(0, 2)).collect()),
+            vec![2, 5, 6]
+        );
+        assert_eq!(grid.positions().count(), 9);
+    }
+}
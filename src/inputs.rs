@@ -0,0 +1,176 @@
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use crate::error::AocError;
+
+/// Directory inputs are read from when nothing else is configured.
+pub const DEFAULT_DIR: &str = "inputs";
+
+/// Environment variable overriding [`DEFAULT_DIR`].
+pub const DIR_VAR: &str = "AOC_INPUT_DIR";
+
+/// Name of the personal puzzle input each day comes with.
+pub const REAL: &str = "real";
+
+const YEAR: u16 = 2023;
+
+/// A directory of puzzle inputs laid out as `<root>/2023/day05.txt` for the real input, and
+/// `<root>/2023/day05.<name>.txt` for any other named input of that day, e.g. `day05.sample.txt`
+/// or `day05.alt-account.txt`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inputs {
+    root: PathBuf,
+}
+
+impl Inputs {
+    pub fn new(root: impl Into<PathBuf>) -> Inputs {
+        Inputs { root: root.into() }
+    }
+
+    /// Inputs from `$AOC_INPUT_DIR`, falling back to `./inputs`.
+    pub fn from_env() -> Inputs {
+        Inputs::new(env::var_os(DIR_VAR).unwrap_or_else(|| DEFAULT_DIR.into()))
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Where the input called `name` for `day` lives, whether or not it exists.
+    pub fn path(&self, day: u8, name: &str) -> PathBuf {
+        let file = if name == REAL {
+            format!("day{:02}.txt", day)
+        } else {
+            format!("day{:02}.{}.txt", day, name)
+        };
+
+        self.root.join(YEAR.to_string()).join(file)
+    }
+
+    /// Reads the input called `name` for `day`, with [`trim_input`] applied.
+    pub fn read(&self, day: u8, name: &str) -> Result<String, AocError> {
+        let path = self.path(day, name);
+
+        match fs::read_to_string(&path) {
+            Ok(input) => Ok(trim_input(&input).to_string()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Err(AocError::MissingInput(path)),
+            Err(e) => Err(AocError::Io(path, e.to_string())),
+        }
+    }
+
+    /// Names of all the inputs present for `day`, sorted.
+    pub fn names(&self, day: u8) -> Vec<String> {
+        let prefix = format!("day{:02}", day);
+
+        let mut names = fs::read_dir(self.root.join(YEAR.to_string()))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| {
+                let file = entry.file_name().into_string().ok()?;
+                let rest = file.strip_prefix(&prefix)?.strip_suffix(".txt")?;
+
+                match rest.strip_prefix('.') {
+                    Some(name) if !name.is_empty() => Some(name.to_string()),
+                    None if rest.is_empty() => Some(REAL.to_string()),
+                    _ => None,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        names.sort();
+        names
+    }
+
+    /// Paths of the input called `name` for each of `days` that doesn't exist.
+    pub fn missing<I>(&self, days: I, name: &str) -> Vec<PathBuf>
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        days.into_iter()
+            .map(|day| self.path(day, name))
+            .filter(|path| !path.is_file())
+            .collect()
+    }
+}
+
+impl Default for Inputs {
+    fn default() -> Self {
+        Inputs::new(DEFAULT_DIR)
+    }
+}
+
+/// Strips trailing line endings, `\n` or `\r\n`, the same as cargo-aoc does before handing
+/// input to a solver.
+pub fn trim_input(input: &str) -> &str {
+    input.trim_end_matches(['\n', '\r'])
+}
+
+/// The sample inputs checked in with the crate.
+#[cfg(test)]
+pub fn fixtures() -> Inputs {
+    Inputs::new(Path::new(env!("CARGO_MANIFEST_DIR")).join(DEFAULT_DIR))
+}
+
+/// An empty input directory for the test called `name` to write inputs into, which the test
+/// removes when it's done.
+#[cfg(test)]
+pub fn scratch_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("aoc-2023-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join(YEAR.to_string())).unwrap();
+    dir
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path() {
+        let inputs = Inputs::new("inputs");
+
+        assert_eq!(inputs.path(5, REAL), Path::new("inputs/2023/day05.txt"));
+        assert_eq!(
+            inputs.path(12, "alt-account"),
+            Path::new("inputs/2023/day12.alt-account.txt")
+        );
+    }
+
+    #[test]
+    fn test_trim_input() {
+        assert_eq!(
+            trim_input("Time: 7\r\nDistance: 9\r\n"),
+            "Time: 7\r\nDistance: 9"
+        );
+        assert_eq!(trim_input("1abc2\n\n"), "1abc2");
+        assert_eq!(trim_input("1abc2"), "1abc2");
+    }
+
+    #[test]
+    fn test_read_and_names() {
+        let dir = scratch_dir("read");
+        fs::write(dir.join("2023/day06.txt"), "Time: 7\nDistance: 9\n\n").unwrap();
+        fs::write(dir.join("2023/day06.sample.txt"), "").unwrap();
+        fs::write(dir.join("2023/day16.txt"), "").unwrap();
+
+        let inputs = Inputs::new(&dir);
+
+        assert_eq!(inputs.read(6, REAL), Ok("Time: 7\nDistance: 9".to_string()));
+        assert_eq!(
+            inputs.read(6, "alt-account"),
+            Err(AocError::MissingInput(
+                dir.join("2023/day06.alt-account.txt")
+            ))
+        );
+        assert_eq!(inputs.names(6), vec!["real", "sample"]);
+        assert_eq!(
+            inputs.missing([5, 6, 7], REAL),
+            vec![dir.join("2023/day05.txt"), dir.join("2023/day07.txt")]
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
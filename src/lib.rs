@@ -3,6 +3,15 @@ extern crate aoc_runner;
 #[macro_use]
 extern crate aoc_runner_derive;
 
+pub mod answers;
+pub mod bench;
+pub mod cards;
+pub mod error;
+pub mod grid;
+pub mod inputs;
+pub mod parsing;
+pub mod solution;
+
 pub mod day01;
 pub mod day02;
 pub mod day03;
@@ -11,4 +20,74 @@ pub mod day05;
 pub mod day06;
 pub mod day07;
 
+use solution::Solver;
+
+/// Every solved day, in order.
+pub static SOLUTIONS: &[&dyn Solver] = &[
+    &day01::Day01,
+    &day02::Day02,
+    &day03::Day03,
+    &day04::Day04,
+    &day05::Day05,
+    &day06::Day06,
+    &day07::Day07,
+];
+
+/// Looks up the solution for `day` in [`SOLUTIONS`].
+pub fn solution(day: u8) -> Option<&'static dyn Solver> {
+    SOLUTIONS.iter().copied().find(|s| s.day() == day)
+}
+
 aoc_lib! { year = 2023 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::answers::{Answers, Status};
+    use crate::solution::Solution;
+
+    fn is_sample(name: &str) -> bool {
+        name.starts_with("sample")
+    }
+
+    #[test]
+    fn test_registry_in_day_order() {
+        let days = SOLUTIONS.iter().map(|s| s.day()).collect::<Vec<_>>();
+
+        assert_eq!(days, (1..=7).collect::<Vec<_>>());
+        assert_eq!(solution(5).map(|s| s.title()), Some(day05::Day05::TITLE));
+        assert!(solution(25).is_none());
+    }
+
+    // Every day's sample inputs (inputs/2023/dayNN.sample*.txt) are checked against the answers
+    // recorded for them in answers.json, so a new day brings its example tests with it.
+    #[test]
+    fn test_sample_fixtures() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let inputs = inputs::fixtures();
+        let answers = Answers::load(root.join(answers::DEFAULT_PATH)).unwrap();
+
+        for solution in SOLUTIONS {
+            assert!(
+                inputs
+                    .names(solution.day())
+                    .iter()
+                    .any(|name| is_sample(name)),
+                "day {} has no sample input in {}",
+                solution.day(),
+                inputs.root().display()
+            );
+        }
+
+        let failures = answers::verify(SOLUTIONS, &inputs, &answers, is_sample)
+            .into_iter()
+            // a sample without a recorded answer checks nothing, so it counts as a failure too
+            .filter(|check| check.status != Status::Pass)
+            .map(|check| check.to_string())
+            .collect::<Vec<_>>();
+
+        assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+    }
+}
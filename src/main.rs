@@ -1,3 +1,501 @@
+use std::io::Read;
+use std::{env, fs, io, process};
+
+use advent_of_code_2023::answers::{self, Answers, Check, Status};
+use advent_of_code_2023::bench::{self, Measurement};
+use advent_of_code_2023::day05::{self, Almanac, Day05, Severity};
+use advent_of_code_2023::inputs::{self, Inputs};
+use advent_of_code_2023::solution::{Part, Solution};
+use advent_of_code_2023::SOLUTIONS;
+
+const USAGE: &str = "usage:
+    advent-of-code-2023 --day <1-25> --part <1|2> [--input <file|-> | --name <input name>] [--dir <input dir>]
+    advent-of-code-2023 verify [--day <1-25>] [--dir <input dir>] [--answers <answers file>]
+    advent-of-code-2023 bench [--day <1-25>] [--part <1|2>] [--name <input name>] [--dir <input dir>] [--runs <n>] [--json]
+    advent-of-code-2023 validate [--input <file|-> | --name <input name>] [--dir <input dir>]
+    advent-of-code-2023 dot [--input <file|-> | --name <input name>] [--dir <input dir>] [--seed <id>]";
+
+const DEFAULT_RUNS: usize = 10;
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Solve,
+    // check every solver against the answers file
+    Verify,
+    // time parsing and solving of every solver over repeated runs
+    Bench,
+    // lint a day 5 almanac
+    Validate,
+    // render a day 5 almanac as a Graphviz graph
+    Dot,
+}
+
+#[derive(Debug, PartialEq)]
+struct Args {
+    command: Command,
+    day: Option<u8>,
+    part: Option<u8>,
+    // read this file (or stdin for "-") instead of a named input from the input directory
+    input: Option<String>,
+    name: String,
+    dir: Option<String>,
+    answers: String,
+    runs: usize,
+    json: bool,
+    // the seed whose path to highlight in a dot graph
+    seed: Option<u64>,
+}
+
+fn parse_args<I>(args: I) -> Result<Args, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut command = Command::Solve;
+    let mut day = None;
+    let mut part = None;
+    let mut input = None;
+    let mut name = None;
+    let mut dir = None;
+    let mut answers = None;
+    let mut runs = None;
+    let mut json = false;
+    let mut seed = None;
+
+    let mut args = args.into_iter().peekable();
+
+    match args.peek().map(String::as_str) {
+        Some("verify") => command = Command::Verify,
+        Some("bench") => command = Command::Bench,
+        Some("validate") => command = Command::Validate,
+        Some("dot") => command = Command::Dot,
+        _ => {}
+    }
+
+    if command != Command::Solve {
+        args.next();
+    }
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("missing value for {}", name))
+        };
+
+        match arg.as_str() {
+            "--day" | "-d" => day = Some(parse_number(&value("--day")?, "--day")?),
+            "--part" | "-p" => part = Some(parse_number(&value("--part")?, "--part")?),
+            "--input" | "-i" => input = Some(value("--input")?),
+            "--name" | "-n" => name = Some(value("--name")?),
+            "--dir" => dir = Some(value("--dir")?),
+            "--answers" => answers = Some(value("--answers")?),
+            "--runs" => {
+                let n = value("--runs")?;
+                let n: usize = n
+                    .parse()
+                    .map_err(|_| format!("invalid value '{}' for --runs", n))?;
+
+                if n == 0 {
+                    return Err("--runs must be at least 1".to_string());
+                }
+
+                runs = Some(n)
+            }
+            "--json" => json = true,
+            "--seed" => {
+                let n = value("--seed")?;
+                seed = Some(
+                    n.parse()
+                        .map_err(|_| format!("invalid value '{}' for --seed", n))?,
+                )
+            }
+            "--help" | "-h" => return Err(USAGE.to_string()),
+            other => return Err(format!("unexpected argument '{}'", other)),
+        }
+    }
+
+    match command {
+        Command::Solve => {
+            day.ok_or("--day is required")?;
+            part.ok_or("--part is required")?;
+
+            if input.is_some() && (name.is_some() || dir.is_some()) {
+                return Err("--input can't be combined with --name or --dir".to_string());
+            }
+        }
+        Command::Verify => {
+            if part.is_some() || input.is_some() || name.is_some() {
+                return Err("verify checks every part of every input, \
+                    only --day, --dir and --answers apply"
+                    .to_string());
+            }
+        }
+        Command::Bench => {
+            if input.is_some() {
+                return Err("bench reads named inputs, use --name instead of --input".to_string());
+            }
+        }
+        Command::Validate | Command::Dot => {
+            if day.is_some() || part.is_some() {
+                return Err("validate and dot only read day 5 almanacs, \
+                    --day and --part don't apply"
+                    .to_string());
+            }
+
+            if input.is_some() && (name.is_some() || dir.is_some()) {
+                return Err("--input can't be combined with --name or --dir".to_string());
+            }
+        }
+    }
+
+    if command != Command::Bench && (runs.is_some() || json) {
+        return Err("--runs and --json only apply to bench".to_string());
+    }
+
+    if command != Command::Dot && seed.is_some() {
+        return Err("--seed only applies to dot".to_string());
+    }
+
+    Ok(Args {
+        command,
+        day,
+        part,
+        input,
+        name: name.unwrap_or_else(|| inputs::REAL.to_string()),
+        dir,
+        answers: answers.unwrap_or_else(|| answers::DEFAULT_PATH.to_string()),
+        runs: runs.unwrap_or(DEFAULT_RUNS),
+        json,
+        seed,
+    })
+}
+
+fn parse_number(s: &str, name: &str) -> Result<u8, String> {
+    s.parse()
+        .map_err(|_| format!("invalid value '{}' for {}", s, name))
+}
+
+// "-" reads the puzzle input from stdin, anything else is a file path
+fn read_input(source: &str) -> Result<String, String> {
+    let input = if source == "-" {
+        let mut buf = String::new();
+        io::stdin()
+            .read_to_string(&mut buf)
+            .map_err(|e| format!("failed to read stdin: {}", e))?;
+        buf
+    } else {
+        fs::read_to_string(source).map_err(|e| format!("failed to read {}: {}", source, e))?
+    };
+
+    Ok(inputs::trim_input(&input).to_string())
+}
+
+impl Args {
+    fn inputs(&self) -> Inputs {
+        self.dir.as_ref().map_or_else(Inputs::from_env, Inputs::new)
+    }
+}
+
+fn solve(args: &Args) -> Result<String, String> {
+    // both are checked by parse_args
+    let (day, part) = (args.day.unwrap_or(0), args.part.unwrap_or(0));
+    let missing = || format!("no solution for day {} part {}", day, part);
+
+    let solution = advent_of_code_2023::solution(day).ok_or_else(missing)?;
+    let part = Part::try_from(part).map_err(|_| missing())?;
+
+    let answer = match &args.input {
+        Some(source) => solution.solve(part, &read_input(source)?),
+        None => solution.solve_named(part, &args.inputs(), &args.name),
+    };
+
+    answer.ok_or_else(missing)?.map_err(|e| e.to_string())
+}
+
+fn verify(args: &Args) -> Result<String, String> {
+    let answers = Answers::load(&args.answers).map_err(|e| format!("{}: {}", args.answers, e))?;
+
+    let solutions = SOLUTIONS
+        .iter()
+        .copied()
+        .filter(|s| args.day.is_none_or(|day| s.day() == day))
+        .collect::<Vec<_>>();
+
+    let checks = answers::verify(&solutions, &args.inputs(), &answers, |_| true);
+
+    let count = |f: fn(&Status) -> bool| checks.iter().filter(|c| f(&c.status)).count();
+    let summary = format!(
+        "{} passed, {} failed, {} unknown, {} missing",
+        count(|s| matches!(s, Status::Pass)),
+        count(|s| matches!(s, Status::Fail { .. } | Status::Error(_))),
+        count(|s| matches!(s, Status::Unknown { .. })),
+        count(|s| matches!(s, Status::Missing { .. })),
+    );
+
+    let report = checks
+        .iter()
+        .map(|c| c.to_string())
+        .chain([summary])
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    if checks.iter().any(Check::failed) {
+        Err(report)
+    } else {
+        Ok(report)
+    }
+}
+
+fn bench(args: &Args) -> Result<String, String> {
+    let inputs = args.inputs();
+    let mut measurements = Vec::new();
+    let mut errors = Vec::new();
+
+    let solutions = SOLUTIONS
+        .iter()
+        .filter(|s| args.day.is_none_or(|day| s.day() == day));
+
+    for solution in solutions {
+        let day = solution.day();
+        let parts = Part::ALL
+            .into_iter()
+            .filter(|p| args.part.is_none_or(|part| p.number() == part));
+
+        let input = match inputs.read(day, &args.name) {
+            Ok(input) => input,
+            Err(e) => {
+                errors.push(format!("day {:02}: {}", day, e));
+                continue;
+            }
+        };
+
+        for part in parts {
+            match solution.bench(part, &input, args.runs) {
+                None => {}
+                Some(Ok(samples)) => {
+                    measurements.extend(Measurement::new(day, part, &args.name, &samples))
+                }
+                Some(Err(e)) => errors.push(format!("day {:02} {}: {}", day, part, e)),
+            }
+        }
+    }
+
+    let report = if args.json {
+        bench::json(&measurements)
+    } else {
+        bench::table(&measurements)
+    };
+
+    if errors.is_empty() {
+        Ok(report)
+    } else {
+        Err(format!("{}\n\n{}", report, errors.join("\n")))
+    }
+}
+
+impl Args {
+    fn almanac(&self) -> Result<Almanac, String> {
+        match &self.input {
+            Some(source) => day05::input_generator(&read_input(source)?),
+            None => Day05::load(&self.inputs(), &self.name),
+        }
+        .map_err(|e| e.to_string())
+    }
+}
+
+fn validate(args: &Args) -> Result<String, String> {
+    let almanac = args.almanac()?;
+    let issues = almanac.validate();
+
+    let count = |severity| {
+        issues
+            .iter()
+            .filter(|i| i.problem.severity() == severity)
+            .count()
+    };
+    let summary = format!(
+        "{} errors, {} warnings, {} notes",
+        count(Severity::Error),
+        count(Severity::Warning),
+        count(Severity::Note),
+    );
+
+    let report = issues
+        .iter()
+        .map(|i| i.to_string())
+        .chain([summary])
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    if count(Severity::Error) > 0 {
+        Err(report)
+    } else {
+        Ok(report)
+    }
+}
+
+fn run(args: &Args) -> Result<String, String> {
+    match args.command {
+        Command::Solve => solve(args),
+        Command::Verify => verify(args),
+        Command::Bench => bench(args),
+        Command::Validate => validate(args),
+        Command::Dot => args.almanac()?.to_dot(args.seed).map_err(|e| e.to_string()),
+    }
+}
+
 fn main() {
-    println!("Hello, world!");
+    match parse_args(env::args().skip(1)).and_then(|args| run(&args)) {
+        Ok(output) => println!("{}", output),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    fn run_with(day: u8, part: u8, dir: &Path, name: &str) -> Result<String, String> {
+        run(&Args {
+            command: Command::Solve,
+            day: Some(day),
+            part: Some(part),
+            input: None,
+            name: name.to_string(),
+            dir: Some(dir.to_string_lossy().to_string()),
+            answers: answers::DEFAULT_PATH.to_string(),
+            runs: DEFAULT_RUNS,
+            json: false,
+            seed: None,
+        })
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            parse_args(args("--day 5 --part 2 --input input/2023/day5.txt")),
+            Ok(Args {
+                command: Command::Solve,
+                day: Some(5),
+                part: Some(2),
+                input: Some("input/2023/day5.txt".to_string()),
+                name: "real".to_string(),
+                dir: None,
+                answers: "answers.json".to_string(),
+                runs: 10,
+                json: false,
+                seed: None,
+            })
+        );
+
+        assert_eq!(
+            parse_args(args("-d 1 -p 1 -n sample --dir puzzles")),
+            Ok(Args {
+                command: Command::Solve,
+                day: Some(1),
+                part: Some(1),
+                input: None,
+                name: "sample".to_string(),
+                dir: Some("puzzles".to_string()),
+                answers: "answers.json".to_string(),
+                runs: 10,
+                json: false,
+                seed: None,
+            })
+        );
+
+        assert_eq!(
+            parse_args(args("verify --day 6 --answers sample.json")),
+            Ok(Args {
+                command: Command::Verify,
+                day: Some(6),
+                part: None,
+                input: None,
+                name: "real".to_string(),
+                dir: None,
+                answers: "sample.json".to_string(),
+                runs: 10,
+                json: false,
+                seed: None,
+            })
+        );
+
+        assert_eq!(
+            parse_args(args("bench --day 5 --name sample --runs 3 --json")),
+            Ok(Args {
+                command: Command::Bench,
+                day: Some(5),
+                part: None,
+                input: None,
+                name: "sample".to_string(),
+                dir: None,
+                answers: "answers.json".to_string(),
+                runs: 3,
+                json: true,
+                seed: None,
+            })
+        );
+
+        assert!(parse_args(args("--day 5 --input -")).is_err());
+        assert!(parse_args(args("--day five --part 1 --input -")).is_err());
+        assert!(parse_args(args("-d 1 -p 1 -i - -n sample")).is_err());
+        assert!(parse_args(args("verify --part 1")).is_err());
+        assert!(parse_args(args("-d 1 -p 1 --runs 3")).is_err());
+        assert_eq!(
+            parse_args(args("bench --runs 0")).map(|a| a.runs),
+            Err("--runs must be at least 1".to_string())
+        );
+        assert!(parse_args(args("validate --day 5")).is_err());
+        assert!(parse_args(args("validate --seed 79")).is_err());
+        assert_eq!(
+            parse_args(args("dot -n sample --seed 79")).map(|a| (a.command, a.seed)),
+            Ok((Command::Dot, Some(79)))
+        );
+        assert_eq!(
+            parse_args(args("validate -n sample")).map(|a| (a.command, a.name)),
+            Ok((Command::Validate, "sample".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_run() {
+        // the checked-in sample fixtures
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join(inputs::DEFAULT_DIR);
+
+        assert_eq!(run_with(6, 1, &fixtures, "sample"), Ok("288".to_string()));
+        assert_eq!(run_with(6, 2, &fixtures, "sample"), Ok("71503".to_string()));
+        assert!(run_with(8, 1, &fixtures, "sample").is_err());
+        assert!(run_with(6, 3, &fixtures, "sample").is_err());
+
+        let dir = env::temp_dir().join(format!("aoc-2023-main-{}", process::id()));
+        fs::create_dir_all(dir.join("2023")).unwrap();
+        fs::write(dir.join("2023/day06.bad.txt"), "Time: 7 15\nDistance: 9 x").unwrap();
+
+        assert_eq!(
+            run_with(6, 1, &dir, "real"),
+            Err(format!(
+                "input file {} not found",
+                dir.join("2023/day06.txt").display()
+            ))
+        );
+        assert_eq!(
+            run_with(6, 1, &dir, "bad"),
+            Err([
+                "parse error at line 2, column 13: expected a number or end of line",
+                " 2 | Distance: 9 x",
+                "   |             ^",
+            ]
+            .join("\n"))
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
 }
@@ -0,0 +1,268 @@
+use std::str::FromStr;
+
+use nom::bytes::complete::{tag, take_till1};
+use nom::character::complete::{char, digit1, line_ending, multispace0, space0, space1};
+use nom::combinator::{eof, map_res, opt, recognize};
+use nom::error::{context, VerboseError};
+use nom::multi::separated_list1;
+use nom::sequence::{pair, preceded, terminated, tuple};
+use nom::{Finish, IResult, Offset, Parser};
+
+use crate::error::{line_and_column, line_at, non_empty, AocError};
+
+/// Result of a nom parser in this crate. Parsers label what they're looking for with
+/// `nom::error::context`, which ends up as the `expected` part of [`AocError::Parse`].
+pub type ParseResult<'a, T> = IResult<&'a str, T, VerboseError<&'a str>>;
+
+/// An unsigned number of any integer type that fits it, e.g. `unsigned::<u8>` fails on "256".
+pub fn unsigned<T: FromStr>(s: &str) -> ParseResult<'_, T> {
+    context("a number", map_res(digit1, T::from_str))(s)
+}
+
+/// A number with an optional leading `-`.
+pub fn signed<T: FromStr>(s: &str) -> ParseResult<'_, T> {
+    context(
+        "a number",
+        map_res(recognize(pair(opt(char('-')), digit1)), T::from_str),
+    )(s)
+}
+
+/// One or more unsigned numbers separated by spaces, e.g. "79 14  55 13".
+pub fn numbers<T: FromStr>(s: &str) -> ParseResult<'_, Vec<T>> {
+    separated_list1(space1, unsigned)(s)
+}
+
+/// One or more signed numbers separated by spaces, e.g. "0 -3 6".
+pub fn signed_numbers<T: FromStr>(s: &str) -> ParseResult<'_, Vec<T>> {
+    separated_list1(space1, signed)(s)
+}
+
+/// The text before a `:` ending a label, e.g. "seed-to-soil map" in "seed-to-soil map:".
+pub fn label(s: &str) -> ParseResult<'_, &str> {
+    context(
+        "a label ending in ':'",
+        terminated(take_till1(|c| c == ':' || c == '\n'), char(':')),
+    )(s)
+}
+
+/// `name` followed by a `:` and then `body`, which can start on the same line after some
+/// spaces or on the next line. Covers "Time:      7  15   30" and "seeds: 79 14" as well as
+/// headed blocks like "seed-to-soil map:\n50 98 2".
+pub fn labelled<'a, O, F>(name: &'static str, body: F) -> impl FnMut(&'a str) -> ParseResult<'a, O>
+where
+    F: Parser<&'a str, O, VerboseError<&'a str>>,
+{
+    preceded(
+        tuple((
+            context(name, tag(name)),
+            context("':'", char(':')),
+            space0,
+            opt(line_ending),
+        )),
+        body,
+    )
+}
+
+/// Runs `parser` over the whole of `input`, failing unless everything but trailing whitespace
+/// gets consumed.
+pub fn parse_all<'a, O, F>(parser: F, input: &'a str) -> Result<O, AocError>
+where
+    F: Parser<&'a str, O, VerboseError<&'a str>>,
+{
+    let end = context("end of input", preceded(multispace0, eof));
+
+    match terminated(parser, end)(non_empty(input)?).finish() {
+        Ok((_, output)) => Ok(output),
+        Err(e) => Err(AocError::from_nom(input, e)),
+    }
+}
+
+/// Parses every line of `input` as a `T`, reporting errors against the line they came from.
+pub fn parse_lines<T>(input: &str) -> Result<Vec<T>, AocError>
+where
+    T: FromStr<Err = AocError>,
+{
+    non_empty(input)?
+        .lines()
+        .enumerate()
+        .map(|(i, line)| T::from_str(line).map_err(|e| e.on_line(i + 1)))
+        .collect()
+}
+
+/// A piece of parsed input copied out along with where it was, so that a problem found with it
+/// once parsing is over can still be reported as a parse error pointing at it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Located {
+    pub text: String,
+    line: usize,
+    column: usize,
+    source_line: String,
+}
+
+impl Located {
+    /// Copies out `at`, which has to be a slice of `input`.
+    pub fn new(input: &str, at: &str) -> Located {
+        let offset = input.offset(at);
+        let (line, column) = line_and_column(input, offset);
+
+        Located {
+            text: at.to_string(),
+            line,
+            column,
+            source_line: line_at(input, offset).to_string(),
+        }
+    }
+
+    /// A parse error pointing at the start of the text.
+    pub fn error(&self, expected: impl Into<String>) -> AocError {
+        AocError::parse(self.line, self.column, expected, &self.source_line)
+    }
+}
+
+/// A run of non-blank lines from a larger input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Block<'a> {
+    /// 1-based line number of the block's first line within the whole input.
+    pub first_line: usize,
+    pub text: &'a str,
+}
+
+impl<'a> Block<'a> {
+    /// [`parse_all`] for just this block, with error line numbers relative to the whole input.
+    pub fn parse<O, F>(&self, parser: F) -> Result<O, AocError>
+    where
+        F: Parser<&'a str, O, VerboseError<&'a str>>,
+    {
+        parse_all(parser, self.text).map_err(|e| e.shift_lines(self.first_line - 1))
+    }
+}
+
+/// Splits `input` into the blocks between blank lines.
+pub fn blocks(input: &str) -> Vec<Block<'_>> {
+    let mut blocks = Vec::new();
+    let mut start: Option<(usize, usize)> = None;
+    let mut offset = 0;
+
+    for (i, line) in input.split_inclusive('\n').enumerate() {
+        let blank = line.trim().is_empty();
+
+        match start {
+            None if !blank => start = Some((i + 1, offset)),
+            Some((first_line, from)) if blank => {
+                blocks.push(block(input, first_line, from, offset));
+                start = None;
+            }
+            _ => {}
+        }
+
+        offset += line.len();
+    }
+
+    if let Some((first_line, from)) = start {
+        blocks.push(block(input, first_line, from, offset));
+    }
+
+    blocks
+}
+
+fn block(input: &str, first_line: usize, from: usize, to: usize) -> Block<'_> {
+    Block {
+        first_line,
+        text: input[from..to].trim_end_matches(['\n', '\r']),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numbers() {
+        assert_eq!(
+            numbers::<u64>("79 14  55 13"),
+            Ok(("", vec![79, 14, 55, 13]))
+        );
+        assert_eq!(
+            signed_numbers::<i32>("0 -3 6 x"),
+            Ok((" x", vec![0, -3, 6]))
+        );
+        assert_eq!(
+            parse_all(unsigned::<u8>, "256"),
+            Err(AocError::parse(1, 1, "a number", "256"))
+        );
+    }
+
+    #[test]
+    fn test_labelled() {
+        assert_eq!(
+            parse_all(labelled("Time", numbers::<u32>), "Time:      7  15   30"),
+            Ok(vec![7, 15, 30])
+        );
+        assert_eq!(
+            parse_all(
+                labelled("seed-to-soil map", numbers::<u32>),
+                "seed-to-soil map:\n50 98 2"
+            ),
+            Ok(vec![50, 98, 2])
+        );
+        assert_eq!(
+            parse_all(labelled("Distance", numbers::<u32>), "Distanse: 9"),
+            Err(AocError::parse(1, 1, "Distance", "Distanse: 9"))
+        );
+        assert_eq!(
+            label("seed-to-soil map:\n1"),
+            Ok(("\n1", "seed-to-soil map"))
+        );
+    }
+
+    #[test]
+    fn test_parse_all() {
+        assert_eq!(parse_all(numbers::<u32>, "1 2 3\n\n"), Ok(vec![1, 2, 3]));
+        assert_eq!(
+            parse_all(numbers::<u32>, "1 2 3 x"),
+            Err(AocError::parse(1, 7, "end of input", "1 2 3 x"))
+        );
+        assert_eq!(parse_all(numbers::<u32>, "\n"), Err(AocError::EmptyInput));
+    }
+
+    #[test]
+    fn test_located() {
+        let input = "Time:\r\n  7  15\r\n";
+        let at = Located::new(input, &input[12..14]);
+
+        assert_eq!(at.text, "15");
+        assert_eq!(
+            at.error("a digit"),
+            AocError::parse(2, 6, "a digit", "  7  15")
+        );
+    }
+
+    #[test]
+    fn test_blocks() {
+        let input = "seeds: 1 2\n\n\na map:\n1 2 3\r\n4 5 6\r\n\r\nb map:\n7 8 9\n";
+        let blocks = blocks(input);
+
+        assert_eq!(
+            blocks,
+            vec![
+                Block {
+                    first_line: 1,
+                    text: "seeds: 1 2"
+                },
+                Block {
+                    first_line: 4,
+                    text: "a map:\n1 2 3\r\n4 5 6"
+                },
+                Block {
+                    first_line: 8,
+                    text: "b map:\n7 8 9"
+                },
+            ]
+        );
+
+        assert_eq!(
+            blocks[1].parse(labelled("a map", numbers::<u32>)),
+            Err(AocError::parse(6, 1, "end of input", "4 5 6"))
+        );
+    }
+}
@@ -0,0 +1,158 @@
+use std::fmt::{self, Display};
+use std::hint::black_box;
+use std::time::Instant;
+
+use crate::bench::Samples;
+use crate::error::AocError;
+use crate::inputs::Inputs;
+
+/// One of the two halves of a day's puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+
+    pub fn number(self) -> u8 {
+        match self {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+}
+
+impl TryFrom<u8> for Part {
+    type Error = u8;
+
+    fn try_from(n: u8) -> Result<Self, Self::Error> {
+        match n {
+            1 => Ok(Part::One),
+            2 => Ok(Part::Two),
+            n => Err(n),
+        }
+    }
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "part {}", self.number())
+    }
+}
+
+/// A day's puzzle: how to turn the raw input into something useful, and how to solve each part
+/// from it. Every `dayNN` module has a unit struct implementing this.
+pub trait Solution {
+    const DAY: u8;
+    const TITLE: &'static str;
+
+    type Input;
+    type Output: Display;
+
+    fn parse(input: &str) -> Result<Self::Input, AocError>;
+
+    /// Reads and parses the input called `name` from `inputs`.
+    fn load(inputs: &Inputs, name: &str) -> Result<Self::Input, AocError> {
+        Self::parse(&inputs.read(Self::DAY, name)?)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Output, AocError>;
+
+    // None for days where part 2 hasn't been solved (yet)
+    fn part2(_input: &Self::Input) -> Option<Result<Self::Output, AocError>> {
+        None
+    }
+}
+
+/// Object-safe view of a [`Solution`], so that days with different input and output types can
+/// live side by side in [`crate::SOLUTIONS`].
+pub trait Solver: Sync {
+    fn day(&self) -> u8;
+
+    fn title(&self) -> &'static str;
+
+    /// Parses `input` and solves `part`, or returns None if this day has no such part.
+    fn solve(&self, part: Part, input: &str) -> Option<Result<String, AocError>>;
+
+    /// Like [`Solver::solve`], with the input called `name` read from `inputs`.
+    fn solve_named(
+        &self,
+        part: Part,
+        inputs: &Inputs,
+        name: &str,
+    ) -> Option<Result<String, AocError>>;
+
+    /// Parses and solves `part` of `input` `runs` times, timing the two phases separately.
+    fn bench(&self, part: Part, input: &str, runs: usize) -> Option<Result<Samples, AocError>>;
+}
+
+impl<S> Solver for S
+where
+    S: Solution + Sync,
+{
+    fn day(&self) -> u8 {
+        S::DAY
+    }
+
+    fn title(&self) -> &'static str {
+        S::TITLE
+    }
+
+    fn solve(&self, part: Part, input: &str) -> Option<Result<String, AocError>> {
+        answer::<S>(part, S::parse(input))
+    }
+
+    fn solve_named(
+        &self,
+        part: Part,
+        inputs: &Inputs,
+        name: &str,
+    ) -> Option<Result<String, AocError>> {
+        answer::<S>(part, S::load(inputs, name))
+    }
+
+    fn bench(&self, part: Part, input: &str, runs: usize) -> Option<Result<Samples, AocError>> {
+        let mut samples = Samples::default();
+
+        for _ in 0..runs {
+            let start = Instant::now();
+            let parsed = match S::parse(black_box(input)) {
+                Ok(parsed) => parsed,
+                Err(e) => return Some(Err(e)),
+            };
+            samples.parse.push(start.elapsed());
+
+            let start = Instant::now();
+            let answer = match part {
+                Part::One => Some(S::part1(black_box(&parsed))),
+                Part::Two => S::part2(black_box(&parsed)),
+            };
+            samples.solve.push(start.elapsed());
+
+            if let Err(e) = answer? {
+                return Some(Err(e));
+            }
+        }
+
+        Some(Ok(samples))
+    }
+}
+
+fn answer<S: Solution>(
+    part: Part,
+    input: Result<S::Input, AocError>,
+) -> Option<Result<String, AocError>> {
+    let input = match input {
+        Ok(input) => input,
+        Err(e) => return Some(Err(e)),
+    };
+
+    let answer = match part {
+        Part::One => Some(S::part1(&input)),
+        Part::Two => S::part2(&input),
+    };
+
+    answer.map(|answer| answer.map(|a| a.to_string()))
+}